@@ -1,15 +1,22 @@
 //! The api module provides the general traits and serves as main interaction point with BitBucket.
 
-use curl::easy::Easy;
+use base64;
 use serde;
 use serde_json;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::thread;
 
+use error::{ApiError, Error};
 use pullrequests::PullrequestsBuilder;
+use ratelimit::{RateLimitStatus, RateLimiter};
 use repositories::RepositoriesBuilder;
-use std::error;
+use retry::RetryPolicy;
 use teams::TeamsBuilder;
+use transport::{CurlTransport, HttpTransport, Method, Request, Response};
+use url::form_urlencoded;
 use users::UsersBuilder;
 
 /// The GetQueryBuilder Trait defines the datastructure returned when passed to API::get.
@@ -18,8 +25,220 @@ pub trait GetQueryBuilder {
     /// The associated type Item defines the data structure returned by the BitBucketQuery
     type Item: serde::de::DeserializeOwned + fmt::Debug;
     /// The get_query method defines the exact Rest API Endpoint to be called
-    /// relative to the API base url https://api.bitbucket.org/v2/
+    /// relative to the API base url, https://api.bitbucket.org/2.0/ unless configured otherwise
     fn get_query(&self) -> BitBucketQuery;
+
+    /// Restricts the fields returned by BitBucket, given as comma separated include (+)
+    /// and exclude (-) paths, e.g. `-values.links,+values.mainbranch`.
+    /// Links and descriptions may be excluded and are then left empty,
+    /// excluding any other field of the Item fails deserialization, see `fields_as`.
+    fn fields<'a>(&'a self, fields: &str) -> WithFields<'a, Self>
+    where
+        Self: Sized,
+    {
+        WithFields::new(self, fields)
+    }
+
+    /// Restricts the fields returned by BitBucket like `fields`, decoding the items as T,
+    /// e.g. a struct of only the requested fields or `serde_json::Value`.
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate serde_derive;
+    /// # extern crate bitbucket_api;
+    /// use bitbucket_api::api::{Api, GetQueryBuilder};
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Slug {
+    ///     slug: String,
+    /// }
+    ///
+    /// # fn main() {
+    /// let api = Api::new("user", "app_password");
+    /// let repositories = api.repositories("team");
+    /// let slugs = repositories.fields_as::<Slug>("values.slug");
+    /// # }
+    /// ```
+    fn fields_as<'a, T>(&'a self, fields: &str) -> FieldsAs<'a, Self, T>
+    where
+        Self: Sized,
+        T: serde::de::DeserializeOwned + fmt::Debug,
+    {
+        FieldsAs {
+            query: WithFields::new(self, fields),
+            item: PhantomData,
+        }
+    }
+
+    /// Sets the number of items per page, overriding the page size chosen by this library.
+    /// BitBucket limits the page size per endpoint, mostly to 50 or 100.
+    fn pagelen<'a>(&'a self, pagelen: usize) -> Paginated<'a, Self>
+    where
+        Self: Sized,
+    {
+        Paginated {
+            query: self,
+            pagelen: Some(pagelen),
+            limit: None,
+        }
+    }
+
+    /// Stops after the given number of items in total, avoiding to fetch further pages.
+    /// Also reduces the page size accordingly. Not applied to `Api::get_page`.
+    fn limit<'a>(&'a self, limit: usize) -> Paginated<'a, Self>
+    where
+        Self: Sized,
+    {
+        Paginated {
+            query: self,
+            pagelen: None,
+            limit: Some(limit),
+        }
+    }
+}
+
+/// The PostQueryBuilder Trait pairs a Rest Endpoint with the JSON body sent by API::post,
+/// and defines the datastructure BitBucket answers with.
+pub trait PostQueryBuilder {
+    /// The associated type Body defines the data structure sent as JSON request body.
+    /// Use `()` for requests without body.
+    type Body: serde::Serialize;
+    /// The associated type Item defines the data structure returned by BitBucket
+    type Item: serde::de::DeserializeOwned + fmt::Debug;
+    /// The post_query method defines the exact Rest API Endpoint to be called,
+    /// see GetQueryBuilder::get_query
+    fn post_query(&self) -> BitBucketQuery;
+    /// The body sent with the request, None sends an empty request
+    fn post_body(&self) -> Option<&Self::Body>;
+}
+
+/// The PutQueryBuilder Trait pairs a Rest Endpoint with the JSON body sent by API::put,
+/// and defines the datastructure BitBucket answers with.
+pub trait PutQueryBuilder {
+    /// The associated type Body defines the data structure sent as JSON request body.
+    type Body: serde::Serialize;
+    /// The associated type Item defines the data structure returned by BitBucket
+    type Item: serde::de::DeserializeOwned + fmt::Debug;
+    /// The put_query method defines the exact Rest API Endpoint to be called,
+    /// see GetQueryBuilder::get_query
+    fn put_query(&self) -> BitBucketQuery;
+    /// The body sent with the request
+    fn put_body(&self) -> &Self::Body;
+}
+
+/// The DeleteQueryBuilder Trait defines the Rest Endpoint of an object removed by API::delete.
+pub trait DeleteQueryBuilder {
+    /// The delete_query method defines the exact Rest API Endpoint to be called,
+    /// see GetQueryBuilder::get_query
+    fn delete_query(&self) -> BitBucketQuery;
+}
+
+/// The RawQueryBuilder Trait defines the Rest Endpoint of a plain text resource,
+/// e.g. a diff, fetched by API::get_raw.
+pub trait RawQueryBuilder {
+    /// The raw_query method defines the exact Rest API Endpoint to be called,
+    /// see GetQueryBuilder::get_query
+    fn raw_query(&self) -> BitBucketQuery;
+}
+
+/// Wraps a query builder, adding the `fields` parameter to its query.
+/// See `GetQueryBuilder::fields`, for other kinds of queries use `WithFields::new`.
+#[derive(Debug)]
+pub struct WithFields<'a, Q: 'a + ?Sized> {
+    query: &'a Q,
+    fields: String,
+}
+
+impl<'a, Q: 'a + ?Sized> WithFields<'a, Q> {
+    /// Wraps the query, requesting only the given fields
+    pub fn new(query: &'a Q, fields: &str) -> WithFields<'a, Q> {
+        WithFields {
+            query,
+            fields: fields.to_owned(),
+        }
+    }
+
+    fn with_fields(&self, query: BitBucketQuery) -> BitBucketQuery {
+        query.with_param("fields", &self.fields)
+    }
+}
+
+impl<'a, Q: GetQueryBuilder + ?Sized> GetQueryBuilder for WithFields<'a, Q> {
+    type Item = Q::Item;
+    fn get_query(&self) -> BitBucketQuery {
+        self.with_fields(self.query.get_query())
+    }
+}
+
+impl<'a, Q: PostQueryBuilder + ?Sized> PostQueryBuilder for WithFields<'a, Q> {
+    type Body = Q::Body;
+    type Item = Q::Item;
+    fn post_query(&self) -> BitBucketQuery {
+        self.with_fields(self.query.post_query())
+    }
+    fn post_body(&self) -> Option<&Q::Body> {
+        self.query.post_body()
+    }
+}
+
+impl<'a, Q: PutQueryBuilder + ?Sized> PutQueryBuilder for WithFields<'a, Q> {
+    type Body = Q::Body;
+    type Item = Q::Item;
+    fn put_query(&self) -> BitBucketQuery {
+        self.with_fields(self.query.put_query())
+    }
+    fn put_body(&self) -> &Q::Body {
+        self.query.put_body()
+    }
+}
+
+/// Wraps a query builder, adding the `fields` parameter to its query and decoding
+/// the items as T, see `GetQueryBuilder::fields_as`.
+#[derive(Debug)]
+pub struct FieldsAs<'a, Q: 'a + ?Sized, T> {
+    query: WithFields<'a, Q>,
+    item: PhantomData<T>,
+}
+
+impl<'a, Q, T> GetQueryBuilder for FieldsAs<'a, Q, T>
+where
+    Q: GetQueryBuilder + ?Sized,
+    T: serde::de::DeserializeOwned + fmt::Debug,
+{
+    type Item = T;
+    fn get_query(&self) -> BitBucketQuery {
+        self.query.get_query()
+    }
+}
+
+/// Wraps a query builder, setting the page size or total number of items,
+/// see `GetQueryBuilder::pagelen` and `GetQueryBuilder::limit`.
+#[derive(Debug)]
+pub struct Paginated<'a, Q: 'a + ?Sized> {
+    query: &'a Q,
+    pagelen: Option<usize>,
+    limit: Option<usize>,
+}
+
+impl<'a, Q: GetQueryBuilder + ?Sized> GetQueryBuilder for Paginated<'a, Q> {
+    type Item = Q::Item;
+    fn get_query(&self) -> BitBucketQuery {
+        let mut query = self.query.get_query();
+        if let Some(pagelen) = self.pagelen {
+            query = query.with_param("pagelen", &pagelen.to_string());
+        }
+        if let Some(limit) = self.limit {
+            // BitBucket uses pages of 10 items, unless requested otherwise
+            let pagelen = query
+                .param("pagelen")
+                .and_then(|pagelen| pagelen.parse().ok())
+                .unwrap_or(10);
+            if limit > 0 && limit < pagelen {
+                query = query.with_param("pagelen", &limit.to_string());
+            }
+            query = query.with_limit(limit);
+        }
+        query
+    }
 }
 
 const HTML_LINK_NAME: &str = "html";
@@ -30,7 +249,7 @@ pub trait HtmlLink {
         if let Some(link) = self.links().get(HTML_LINK_NAME) {
             match link {
                 Link::Link {href,..} => Some(href),
-                Link::Multi(_) => unreachable!("{} can never be Multi Link variant")
+                Link::Multi(_) => unreachable!("{} can never be Multi Link variant", HTML_LINK_NAME)
             }
         } else {
             None
@@ -41,21 +260,136 @@ pub trait HtmlLink {
 
 }
 
+/// Base URL of the public BitBucket Cloud API
+pub const DEFAULT_BASE_URL: &str = "https://api.bitbucket.org/2.0/";
+
+/// ApiBuilder is used to configure an Api Object, e.g. to point it at a local mock or a proxy.
+/// ```
+/// use bitbucket_api::api::ApiBuilder;
+///
+/// let api = ApiBuilder::new("user", "app_password")
+///     .base_url("http://localhost:8080/2.0")
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ApiBuilder {
+    user: String,
+    key: String,
+    base_url: String,
+    transport: Option<Arc<dyn HttpTransport>>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl ApiBuilder {
+    /// Get a new ApiBuilder by passing Username and Bitbucket App Password.
+    /// Please do NOT use your account password.
+    pub fn new(user: &str, key: &str) -> ApiBuilder {
+        ApiBuilder {
+            user: user.to_owned(),
+            key: key.to_owned(),
+            base_url: DEFAULT_BASE_URL.to_owned(),
+            transport: None,
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+        }
+    }
+
+    /// Sets the URL relative query paths are resolved against. Defaults to `DEFAULT_BASE_URL`.
+    /// Absolute URLs, e.g. the `next` links of paged responses, are always used as is.
+    pub fn base_url(&mut self, base_url: &str) -> &mut ApiBuilder {
+        let mut base_url = base_url.to_owned();
+        if !base_url.ends_with('/') {
+            base_url.push('/');
+        }
+        self.base_url = base_url;
+        self
+    }
+
+    /// Sends all requests through the given transport instead of libcurl,
+    /// e.g. a `transport::MemoryTransport` for tests.
+    pub fn transport<T>(&mut self, transport: T) -> &mut ApiBuilder
+    where
+        T: HttpTransport + 'static,
+    {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sets how temporarily failing requests are retried, defaults to `RetryPolicy::default()`.
+    /// Use `RetryPolicy::none()` to disable retries.
+    pub fn retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut ApiBuilder {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Limits the number of requests sent by the Api and all its clones.
+    /// By default requests are not limited.
+    pub fn rate_limiter(&mut self, rate_limiter: RateLimiter) -> &mut ApiBuilder {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Creates the configured Api Object
+    pub fn build(&self) -> Api {
+        Api {
+            user: self.user.to_owned(),
+            key: self.key.to_owned(),
+            base_url: self.base_url.to_owned(),
+            transport: match self.transport {
+                Some(ref transport) => Arc::clone(transport),
+                None => Arc::new(CurlTransport::new()),
+            },
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter.clone(),
+            rate_limit_status: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
 /// Api is used to handle authentication. This part may be subject to change very soon.
+/// All requests are sent through an HttpTransport, which defaults to libcurl.
 #[derive(Debug, Clone)]
 pub struct Api {
     user: String,
     key: String,
+    base_url: String,
+    transport: Arc<dyn HttpTransport>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    rate_limit_status: Arc<Mutex<Option<RateLimitStatus>>>,
 }
 
 impl Api {
     /// Get a new API Object by passing Username and Bitbucket App Password.
     /// Please do NOT use your account password.
     pub fn new(user: &str, key: &str) -> Api {
-        Api {
-            user: user.to_owned(),
-            key: key.to_owned(),
-        }
+        ApiBuilder::new(user, key).build()
+    }
+    /// Get a new API Object sending all requests through the given transport,
+    /// e.g. a `transport::MemoryTransport` for tests.
+    pub fn with_transport<T>(user: &str, key: &str, transport: T) -> Api
+    where
+        T: HttpTransport + 'static,
+    {
+        ApiBuilder::new(user, key).transport(transport).build()
+    }
+    /// Get an ApiBuilder for further configuration, see `ApiBuilder`
+    pub fn builder(user: &str, key: &str) -> ApiBuilder {
+        ApiBuilder::new(user, key)
+    }
+    /// The URL relative query paths are resolved against
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    /// The rate limit headers of the latest response carrying any,
+    /// to budget the remaining requests.
+    pub fn rate_limit_status(&self) -> Option<RateLimitStatus> {
+        let status = self
+            .rate_limit_status
+            .lock()
+            .expect("Rate limit status lock poisoned");
+        status.clone()
     }
     /// Operations on Repositories Endpoint
     pub fn repositories(&self, username: &str) -> RepositoriesBuilder {
@@ -90,136 +424,478 @@ impl Api {
     /// let user = api.get(&query);
     /// assert!(user.is_ok());
     /// ```
-    pub fn get<T>(&self, query: &GetQueryBuilder<Item = T>) -> Result<Vec<T>, Box<error::Error>>
+    pub fn get<T>(&self, query: &dyn GetQueryBuilder<Item = T>) -> Result<Vec<T>, Error>
     where
         T: serde::de::DeserializeOwned + fmt::Debug,
     {
-        let query = query.get_query();
-        let data = self.get_curl_data(&query)?;
-        debug!("{}", String::from_utf8_lossy(&data));
-        let content: BitBucketResponse<T> = serde_json::from_slice(&data)?;
-
-        let data = match content {
-            BitBucketResponse::Item(content) => vec![content],
-            BitBucketResponse::Paged { values, next, .. } => {
-                let mut v = values;
-                self.append_paged_data(&mut v, next.to_owned())?;
-                v
-            }
+        self.iter(query).collect()
+    }
+
+    /// Iterates lazily over all items returned by a query.
+    /// Further pages are only fetched once the items of the previous page are consumed,
+    /// so stopping early, e.g. using `take` or `find`, avoids unnecessary requests.
+    /// After an error the iterator ends.
+    /// ```
+    /// use bitbucket_api::api::Api;
+    ///
+    /// # use bitbucket_api::test_utils;
+    /// # let env = test_utils::get_test_env();
+    /// let api = Api::new(&env.user, &env.api_key);
+    /// let query = api.repositories(&env.team);
+    ///
+    /// for repository in api.iter(&query).take(10) {
+    ///     println!("{}", repository.expect("Repository query must not fail").name);
+    /// }
+    /// ```
+    pub fn iter<T>(&self, query: &dyn GetQueryBuilder<Item = T>) -> Iter<T>
+    where
+        T: serde::de::DeserializeOwned + fmt::Debug,
+    {
+        self.iter_query(query.get_query())
+    }
+
+    pub(crate) fn iter_query<T>(&self, query: BitBucketQuery) -> Iter<T> {
+        Iter {
+            api: self.clone(),
+            remaining: query.limit(),
+            next: Some(query),
+            first_page: true,
+            items: VecDeque::new(),
+        }
+    }
+
+    /// Fetches a single page of a paged query, page numbers start at 1.
+    /// Besides the items, the page reports the total number of items, if known,
+    /// and the URL of the next page.
+    /// ```
+    /// use bitbucket_api::api::Api;
+    ///
+    /// # use bitbucket_api::test_utils;
+    /// # let env = test_utils::get_test_env();
+    /// let api = Api::new(&env.user, &env.api_key);
+    /// let query = api.repositories(&env.team);
+    ///
+    /// let page = api.get_page(&query, 1).expect("Repository query must not fail");
+    /// println!("{} of {:?} repositories", page.values.len(), page.size);
+    /// ```
+    pub fn get_page<T>(
+        &self,
+        query: &dyn GetQueryBuilder<Item = T>,
+        page_number: usize,
+    ) -> Result<Page<T>, Error>
+    where
+        T: serde::de::DeserializeOwned + fmt::Debug,
+    {
+        let query = query
+            .get_query()
+            .with_param("page", &page_number.to_string());
+        match self.get_data(&query)? {
+            BitBucketResponse::Paged(page) => Ok(page),
+            BitBucketResponse::Item(_) => Err(Error::Pagination {
+                url: query.get_url(&self.base_url),
+                message: "Single value response for paged query".to_owned(),
+            }),
+        }
+    }
+
+    /// Fetches one page of a query. Single item responses are only expected as answer
+    /// to the first request and are returned as a page containing only this item.
+    pub(crate) fn fetch_page<T>(
+        &self,
+        query: &BitBucketQuery,
+        first_page: bool,
+    ) -> Result<Page<T>, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        match self.get_data(query)? {
+            BitBucketResponse::Paged(page) => Ok(page),
+            BitBucketResponse::Item(item) if first_page => Ok(Page {
+                page: None,
+                pagelen: None,
+                size: Some(1),
+                values: vec![item],
+                next: None,
+                previous: None,
+            }),
+            BitBucketResponse::Item(_) => Err(Error::Pagination {
+                url: query.get_url(&self.base_url),
+                message: "Single value on next of multi value query".to_owned(),
+            }),
+        }
+    }
+
+    /// Fetches a single item or a page, telling them apart by the `values` key of pages
+    fn get_data<T>(&self, query: &BitBucketQuery) -> Result<BitBucketResponse<T>, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let (url, body) = self.send_json_request(Method::Get, query, None)?;
+        match serde_json::from_slice::<PageProbe>(&body) {
+            Ok(PageProbe { values: Some(_) }) => decode(&url, &body).map(BitBucketResponse::Paged),
+            _ => decode(&url, &body).map(BitBucketResponse::Item),
+        }
+    }
+
+    /// This is the main Interface for POST requests, creating objects or triggering actions.
+    /// The body of the query is sent as JSON, the response is returned as the query's Item.
+    pub fn post<B, T>(&self, query: &dyn PostQueryBuilder<Body = B, Item = T>) -> Result<T, Error>
+    where
+        B: serde::Serialize,
+        T: serde::de::DeserializeOwned + fmt::Debug,
+    {
+        self.send_json(Method::Post, &query.post_query(), query.post_body())
+    }
+
+    /// This is the main Interface for PUT requests, updating existing objects.
+    /// The body of the query is sent as JSON, the response is returned as the query's Item.
+    pub fn put<B, T>(&self, query: &dyn PutQueryBuilder<Body = B, Item = T>) -> Result<T, Error>
+    where
+        B: serde::Serialize,
+        T: serde::de::DeserializeOwned + fmt::Debug,
+    {
+        self.send_json(Method::Put, &query.put_query(), Some(query.put_body()))
+    }
+
+    /// This is the main Interface for DELETE requests, removing objects.
+    pub fn delete(&self, query: &dyn DeleteQueryBuilder) -> Result<(), Error> {
+        self.send_json_bytes(Method::Delete, &query.delete_query(), None)
+            .map(|_: serde_json::Value| ())
+    }
+
+    /// This is the main Interface for resources which are not JSON, e.g. diffs and patches.
+    /// The response is returned as text, invalid UTF-8 is replaced.
+    pub fn get_raw(&self, query: &dyn RawQueryBuilder) -> Result<String, Error> {
+        self.send_raw(&query.raw_query())
+    }
+
+    pub(crate) fn send_raw(&self, query: &BitBucketQuery) -> Result<String, Error> {
+        let url = query.get_url(&self.base_url);
+        info!("CALLING {} {}\n", Method::Get, url);
+        let request = self.request(Method::Get, url, "text/plain");
+        let response = self.send(&request)?;
+        Ok(String::from_utf8_lossy(&response.body).into_owned())
+    }
+
+    pub(crate) fn send_json<B, T>(
+        &self,
+        method: Method,
+        query: &BitBucketQuery,
+        body: Option<&B>,
+    ) -> Result<T, Error>
+    where
+        B: serde::Serialize,
+        T: serde::de::DeserializeOwned,
+    {
+        let body = match body {
+            Some(body) => Some(serde_json::to_vec(body).map_err(Error::Serialize)?),
+            None => None,
         };
-        Ok(data)
+        self.send_json_bytes(method, query, body)
     }
 
-    fn append_paged_data<T>(
+    /// Sends an already serialized JSON body, decoding the JSON response.
+    /// Empty responses are decoded as `null`, e.g. into `()`.
+    pub(crate) fn send_json_bytes<T>(
         &self,
-        data: &mut Vec<T>,
-        next: Option<String>,
-    ) -> Result<(), Box<error::Error>>
+        method: Method,
+        query: &BitBucketQuery,
+        body: Option<Vec<u8>>,
+    ) -> Result<T, Error>
     where
         T: serde::de::DeserializeOwned,
     {
-        let mut next_url = next;
-        while let Some(url) = next_url {
-            let request = BitBucketQuery::new(url.to_owned());
-            let mut raw_data = self.get_curl_data(&request)?;
-            debug!("{}\n", String::from_utf8_lossy(&raw_data));
-
-            let paged_content: BitBucketResponse<T> = serde_json::from_slice(&raw_data)?;
-            match paged_content {
-                BitBucketResponse::Paged {
-                    mut values, next, ..
-                } => {
-                    data.append(&mut values);
-                    next_url = next.to_owned()
-                }
-                BitBucketResponse::Item(_) => panic!("Single value on next of multi value query"),
+        let (url, body) = self.send_json_request(method, query, body)?;
+        decode(&url, &body)
+    }
+
+    /// Sends a JSON request, returning the URL and the response body, `null` if empty.
+    fn send_json_request(
+        &self,
+        method: Method,
+        query: &BitBucketQuery,
+        body: Option<Vec<u8>>,
+    ) -> Result<(String, Vec<u8>), Error> {
+        let url = query.get_url(&self.base_url);
+        info!("CALLING {} {}\n", method, url);
+        let mut request = self.request(method, url, "application/json");
+        if let Some(body) = body {
+            debug!("{}", String::from_utf8_lossy(&body));
+            request
+                .headers
+                .push(("Content-Type".to_owned(), "application/json".to_owned()));
+            request.body = Some(body);
+        }
+        let response = self.send(&request)?;
+        debug!("{}", String::from_utf8_lossy(&response.body));
+
+        let body = if response.body.is_empty() {
+            b"null".to_vec()
+        } else {
+            response.body
+        };
+        Ok((request.url, body))
+    }
+
+    /// Sends the request, retrying according to the retry policy.
+    /// Unsuccessful responses are returned as Error::Http.
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let mut attempt = 1;
+        loop {
+            if let Some(ref rate_limiter) = self.rate_limiter {
+                rate_limiter.acquire();
             }
+            let (reason, delay) = match self.transport.send(request) {
+                Ok(response) => {
+                    self.update_rate_limit_status(&response);
+                    if response.status >= 200 && response.status < 300 {
+                        return Ok(response);
+                    }
+                    let status = Some(response.status);
+                    if !self
+                        .retry_policy
+                        .should_retry(request.method, attempt, status)
+                    {
+                        return Err(ApiError::from_response(&request.url, &response).into());
+                    }
+                    match self.retry_policy.delay(attempt, Some(&response)) {
+                        Some(delay) => (format!("status {}", response.status), delay),
+                        None => {
+                            return Err(ApiError::from_response(&request.url, &response).into())
+                        }
+                    }
+                }
+                Err(e) => {
+                    if !self
+                        .retry_policy
+                        .should_retry(request.method, attempt, None)
+                    {
+                        return Err(e.into());
+                    }
+                    match self.retry_policy.delay(attempt, None) {
+                        Some(delay) => (e.to_string(), delay),
+                        None => return Err(e.into()),
+                    }
+                }
+            };
+            warn!(
+                "{} {} failed ({}), retrying in {:?}",
+                request.method, request.url, reason, delay
+            );
+            thread::sleep(delay);
+            attempt += 1;
         }
-        Ok(())
     }
 
-    fn get_curl_data(&self, query: &BitBucketQuery) -> Result<Vec<u8>, Box<error::Error>> {
-        let url = query.get_url();
-        info!("CALLING {}\n", url);
-        let mut handle = self.get_curl_handle(&url)?;
-        let mut buf = Vec::new();
-        fetch_curl_data(&mut handle, &mut buf)?;
-        Ok(buf)
+    fn update_rate_limit_status(&self, response: &Response) {
+        if let Some(status) = RateLimitStatus::from_response(response) {
+            let mut current = self
+                .rate_limit_status
+                .lock()
+                .expect("Rate limit status lock poisoned");
+            *current = Some(status);
+        }
     }
 
-    fn get_curl_handle(&self, url: &str) -> Result<Easy, Box<error::Error>> {
-        let mut handle = Easy::new();
-        handle.accept_encoding("application/json")?;
-        handle.username(&self.user)?;
-        handle.password(&self.key)?;
-        handle.url(url)?;
-        Ok(handle)
+    fn request(&self, method: Method, url: String, accept: &str) -> Request {
+        let credentials = base64::encode(&format!("{}:{}", self.user, self.key));
+        Request {
+            method,
+            url,
+            headers: vec![
+                ("Accept".to_owned(), accept.to_owned()),
+                ("Authorization".to_owned(), format!("Basic {}", credentials)),
+            ],
+            body: None,
+        }
     }
 }
 
-fn fetch_curl_data(handle: &mut Easy, buf: &mut Vec<u8>) -> Result<(), Box<error::Error>> {
-    let mut transfer = handle.transfer();
-    transfer.write_function(|data| {
-        buf.extend_from_slice(data);
-        Ok(data.len())
-    })?;
-    transfer.perform()?;
-    Ok(())
+/// Iterator over the items of a query, fetching pages on demand. See `Api::iter`.
+#[derive(Debug)]
+pub struct Iter<T> {
+    api: Api,
+    remaining: Option<usize>,
+    next: Option<BitBucketQuery>,
+    first_page: bool,
+    items: VecDeque<T>,
+}
+
+impl<T> Iterator for Iter<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        loop {
+            if self.remaining == Some(0) {
+                return None;
+            }
+            if let Some(item) = self.items.pop_front() {
+                self.remaining = self.remaining.map(|remaining| remaining - 1);
+                return Some(Ok(item));
+            }
+            let query = self.next.take()?;
+            let first_page = self.first_page;
+            self.first_page = false;
+
+            match self.api.fetch_page(&query, first_page) {
+                Ok(page) => {
+                    self.items.extend(page.values);
+                    self.next = page.next.map(BitBucketQuery::new);
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
 }
 
 /// This is a simple structure for constructing a Rest API Endpoint URL/URI in String Format.
 #[derive(Debug, Clone)]
 pub struct BitBucketQuery {
     url_path: String,
+    limit: Option<usize>,
 }
 
 impl BitBucketQuery {
     pub(crate) fn new(url_path: String) -> BitBucketQuery {
-        BitBucketQuery { url_path }
+        BitBucketQuery {
+            url_path,
+            limit: None,
+        }
+    }
+
+    /// Limits the total number of items returned for the query
+    pub(crate) fn with_limit(mut self, limit: usize) -> BitBucketQuery {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub(crate) fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Returns the first value of a query parameter, as it appears in the URL.
+    pub(crate) fn param(&self, key: &str) -> Option<&str> {
+        let params = &self.url_path[self.url_path.find('?')? + 1..];
+        let prefix = format!("{}=", key);
+        params
+            .split('&')
+            .find(|param| param.starts_with(&prefix))
+            .map(|param| &param[prefix.len()..])
     }
 
-    /// Appends relative URLs to the API base URL, or returns absolute URLs as is.
-    pub fn get_url(&self) -> String {
+    /// Appends a URL encoded query parameter, replacing earlier values of the same key.
+    pub(crate) fn with_param(self, key: &str, value: &str) -> BitBucketQuery {
+        let (path, params) = match self.url_path.find('?') {
+            Some(pos) => (&self.url_path[..pos], &self.url_path[pos + 1..]),
+            None => (&self.url_path[..], ""),
+        };
+        let prefix = format!("{}=", key);
+        let mut params: Vec<String> = params
+            .split('&')
+            .filter(|param| !param.is_empty() && !param.starts_with(&prefix))
+            .map(|param| param.to_owned())
+            .collect();
+        let value: String = form_urlencoded::byte_serialize(value.as_bytes()).collect();
+        params.push(format!("{}{}", prefix, value));
+        BitBucketQuery {
+            url_path: format!("{}?{}", path, params.join("&")),
+            limit: self.limit,
+        }
+    }
+
+    /// Appends a URL encoded query parameter, keeping earlier values of the same key.
+    pub(crate) fn add_param(self, key: &str, value: &str) -> BitBucketQuery {
+        let value: String = form_urlencoded::byte_serialize(value.as_bytes()).collect();
+        let separator = if self.url_path.contains('?') { '&' } else { '?' };
+        BitBucketQuery {
+            url_path: format!("{}{}{}={}", self.url_path, separator, key, value),
+            limit: self.limit,
+        }
+    }
+
+    /// Appends relative URLs to the given API base URL, or returns absolute URLs as is.
+    pub fn get_url(&self, base_url: &str) -> String {
         if self.url_path.starts_with("http://") || self.url_path.starts_with("https://") {
             self.url_path.to_owned()
         } else {
-            format!("https://api.bitbucket.org/2.0/{}", self.url_path)
+            format!("{}{}", base_url, self.url_path)
         }
     }
 }
 
 /// This is the main wrapper type for (successful) BitBucket API Responses.
 /// For now it handles either single Item Responses, as well as Paged Multi-Item Responses.
-/// This will most likely be subject to change very soon.
-#[derive(Debug, Clone, Deserialize)]
-#[serde(untagged)]
+/// Responses with a `values` key are pages, see `Api::get_data`.
+#[derive(Debug, Clone)]
 pub(crate) enum BitBucketResponse<T> {
     /// The Paged enum variant corresponds to a paged BitBucket API Response.
-    /// page and size are currently unused values. All values are gathered following the
-    /// next value which contains the URL to the next page or null.
-    Paged {
-        /// Number of current page
-        page: Option<usize>,
-        /// Number of Items on current page
-        pagelen: Option<usize>,
-        /// Total number of Items in the Query
-        size: Option<usize>,
-        /// Values
-        values: Vec<T>,
-        /// URL to the next page containing the rest of the Items or nothing.
-        next: Option<String>,
-    },
+    Paged(Page<T>),
     /// The Item enum variant corresponds to a single Item BitBucket API Response.
     Item(T),
 }
 
+/// Only checks for the `values` key, telling pages apart from single items
+#[derive(Deserialize)]
+struct PageProbe {
+    values: Option<serde::de::IgnoredAny>,
+}
+
+/// Decodes a JSON response body, locating the mismatching field on failure
+fn decode<T>(url: &str, body: &[u8]) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_slice(body).map_err(|source| Error::deserialize(url, body, source))
+}
+
+/// A single page of a paged BitBucket API Response, see `Api::get_page`.
+/// BitBucket may omit page, pagelen and size for some endpoints.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Page<T> {
+    /// Number of current page, starting at 1
+    pub page: Option<usize>,
+    /// Maximum number of Items per page
+    pub pagelen: Option<usize>,
+    /// Total number of Items in the Query
+    pub size: Option<usize>,
+    /// Items on the current page
+    pub values: Vec<T>,
+    /// URL to the next page containing the rest of the Items or nothing.
+    pub next: Option<String>,
+    /// URL to the previous page or nothing.
+    pub previous: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Returns the total number of pages, if BitBucket reported the total size.
+    pub fn page_count(&self) -> Option<usize> {
+        match (self.size, self.pagelen) {
+            (Some(size), Some(pagelen)) if pagelen > 0 => Some(size.div_ceil(pagelen)),
+            _ => None,
+        }
+    }
+}
+
 /*
  * Common BitBucket JSON structures
  */
 
 // Reexport Specific DS
-pub use pullrequests::PullRequest;
-pub use repositories::branchrestrictions::BranchPermission;
+pub use pullrequests::{
+    MergeStrategy, Participant, ParticipantRole, ParticipantState, PullRequest, PullRequestState,
+};
+pub use repositories::branchrestrictions::{
+    BranchMatchKind, BranchPermission, BranchRestrictionKind, BranchRestrictionSpec, BranchType,
+    Group, GroupRef, UserRef,
+};
+pub use repositories::pullrequest::activity::PullRequestActivity;
+pub use repositories::pullrequest::comments::{Comment, CommentRef, Inline};
+pub use repositories::pullrequest::diff::{CommitFile, DiffStat, DiffStatus};
+pub use repositories::pullrequest::statuses::{CommitStatus, CommitStatusState};
+pub use repositories::refs::{Ref, Tag};
 pub use repositories::Repository;
 pub use users::User;
 
@@ -234,22 +910,108 @@ pub enum Link {
     Link { href: String, name: Option<String> },
 }
 
-/// Branch defined by its name
+/// Point in time as returned by BitBucket.
+/// A `chrono::DateTime<Utc>` with the `chrono` feature, otherwise the ISO 8601 string.
+#[cfg(feature = "chrono")]
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+/// Point in time as returned by BitBucket.
+/// A `chrono::DateTime<Utc>` with the `chrono` feature, otherwise the ISO 8601 string.
+#[cfg(not(feature = "chrono"))]
+pub type Timestamp = String;
+
+/// Text content, e.g. of a comment, in its raw form and rendered to HTML
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Rendered {
+    /// Content as entered by the user
+    #[serde(default)]
+    pub raw: String,
+    /// Markup language of the raw content, e.g. markdown
+    pub markup: Option<String>,
+    /// Content rendered to HTML
+    pub html: Option<String>,
+}
+
+/// Branch defined by its name.
+/// Further details are only included when listing or fetching branches.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Branch {
     /// Name of the Branch
     pub name: String,
+    /// The commit the Branch points to
+    pub target: Option<Commit>,
+    /// Strategies allowed when merging a Pullrequest into the Branch
+    #[serde(default)]
+    pub merge_strategies: Vec<MergeStrategy>,
+    #[allow(missing_docs)]
+    pub default_merge_strategy: Option<MergeStrategy>,
+    #[allow(missing_docs)]
+    #[serde(default)]
+    pub links: HashMap<String, Link>,
 }
 
-/// Commit hash with Links
+/// Commit hash with Links.
+/// Further details are only included by some endpoints, e.g. when listing commits.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Commit {
     /// Digest representing a specific commit commit
     pub hash: String,
     /// Various Links related to a specific commit
+    #[serde(default)]
+    pub links: HashMap<String, Link>,
+    #[allow(missing_docs)]
+    pub message: Option<String>,
+    #[allow(missing_docs)]
+    pub author: Option<Author>,
+    #[allow(missing_docs)]
+    pub date: Option<Timestamp>,
+    /// Parent commits, only carrying hash and links
+    #[serde(default)]
+    pub parents: Vec<Commit>,
+    /// The commit message rendered as markdown
+    pub summary: Option<Rendered>,
+}
+
+impl HtmlLink for Commit {
+    fn links(&self) -> &HashMap<String, Link> {
+        &self.links
+    }
+}
+
+/// Author of a commit or tag, linked to a User if BitBucket knows the email address
+#[derive(Debug, Clone, Deserialize)]
+pub struct Author {
+    /// Name and email as recorded by git, e.g. `Jane Doe <jane@example.com>`
+    pub raw: String,
+    #[allow(missing_docs)]
+    pub user: Option<User>,
+}
+
+impl fmt::Display for Author {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.user {
+            Some(ref user) => write!(f, "{}", user),
+            None => write!(f, "{}", self.raw),
+        }
+    }
+}
+
+/// Reference to a Repository, as embedded in other objects
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct RepositoryRef {
+    pub name: String,
+    pub full_name: String,
+    pub uuid: String,
+    #[serde(default)]
     pub links: HashMap<String, Link>,
 }
 
+impl HtmlLink for RepositoryRef {
+    fn links(&self) -> &HashMap<String, Link> {
+        &self.links
+    }
+}
+
 /// Represents PullRequest data structure, Pointing to Source/Destination Branch,Commit,Repo
 /// This will probably be moved into pullrequests
 #[derive(Debug, Clone, Deserialize)]
@@ -257,5 +1019,452 @@ pub struct Commit {
 pub struct Sourctination {
     pub branch: Branch,
     pub commit: Commit,
-    pub repository: serde_json::Value,
+    pub repository: RepositoryRef,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use transport::MemoryTransport;
+
+    const USER_JSON: &[u8] = br#"{"username":"tester","nickname":"tester","display_name":"Tester","uuid":"{1}","links":{}}"#;
+
+    #[test]
+    fn requests_are_authenticated() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(200, USER_JSON),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+
+        let users = api.get(&api.user("tester")).unwrap();
+
+        assert_eq!(users[0].display_name, "Tester");
+        let requests = transport.requests();
+        assert_eq!(
+            requests[0].header("Authorization"),
+            Some("Basic dGVzdGVyOnNlY3JldA==")
+        );
+    }
+
+    #[test]
+    fn paged_responses_are_followed() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members",
+            Response::new(
+                200,
+                br#"{"values":[{"username":"a","nickname":"a","display_name":"A","uuid":"{a}","links":{}}],
+                    "next":"https://api.bitbucket.org/2.0/teams/team/members?page=2"}"#,
+            ),
+        );
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members?page=2",
+            Response::new(
+                200,
+                br#"{"values":[{"username":"b","nickname":"b","display_name":"B","uuid":"{b}","links":{}}]}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+
+        let members = api.get(&api.team("team").members()).unwrap();
+
+        let names: Vec<&str> = members.iter().map(|u| u.username.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn single_pages_can_be_requested() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members?page=3",
+            Response::new(
+                200,
+                br#"{"page":3,"pagelen":1,"size":5,"values":[{"username":"c","nickname":"c","display_name":"C","uuid":"{c}","links":{}}],
+                    "next":"https://api.bitbucket.org/2.0/teams/team/members?page=4"}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        let page = api.get_page(&api.team("team").members(), 3).unwrap();
+
+        assert_eq!(page.page, Some(3));
+        assert_eq!(page.size, Some(5));
+        assert_eq!(page.page_count(), Some(5));
+        assert_eq!(page.values[0].username, "c");
+    }
+
+    #[test]
+    fn query_params_are_replaced() {
+        let query = BitBucketQuery::new("repositories/team?pagelen=75&page=2".to_owned())
+            .with_param("page", "3")
+            .with_param("q", r#"name~"a b""#);
+        assert_eq!(
+            query.get_url(""),
+            "repositories/team?pagelen=75&page=3&q=name%7E%22a+b%22"
+        );
+    }
+
+    #[test]
+    fn fields_trim_responses() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team?pagelen=75&fields=-values.links%2C%2Bvalues.mainbranch",
+            Response::new(
+                200,
+                br#"{"values":[{"name":"repo","full_name":"team/repo","uuid":"{1}","slug":"repo","is_private":true,"mainbranch":{"name":"master"}}]}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+        let repositories = api.repositories("team");
+
+        let repositories = api
+            .get(&repositories.fields("-values.links,+values.mainbranch"))
+            .unwrap();
+
+        assert_eq!(repositories[0].slug, "repo");
+        assert_eq!(repositories[0].mainbranch.as_ref().unwrap().name, "master");
+        assert!(repositories[0].links.is_empty());
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Slug {
+        slug: String,
+    }
+
+    #[test]
+    fn fields_as_decodes_trimmed_items() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team?pagelen=75&fields=values.slug",
+            Response::new(200, br#"{"values":[{"slug":"repo"},{"slug":"api"}]}"#),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+        let repositories = api.repositories("team");
+
+        let slugs = api
+            .get(&repositories.fields_as::<Slug>("values.slug"))
+            .unwrap();
+        assert_eq!(slugs[1].slug, "api");
+
+        let values = api
+            .get(&repositories.fields_as::<serde_json::Value>("values.slug"))
+            .unwrap();
+        assert_eq!(values[0]["slug"], "repo");
+    }
+
+    #[test]
+    fn invalid_values_fail_the_page() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team?pagelen=75",
+            Response::new(
+                200,
+                br#"{"values":[{"name":"repo","full_name":"team/repo","uuid":"{1}","slug":"repo","is_private":"yes"}]}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        assert!(api.get(&api.repositories("team")).is_err());
+    }
+
+    #[test]
+    fn deserialize_errors_name_the_field() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team?pagelen=75",
+            Response::new(
+                200,
+                br#"{"values":[{"name":"repo","full_name":"team/repo","uuid":"{1}","slug":"repo","is_private":"yes"}]}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        let error = api.get(&api.repositories("team")).unwrap_err();
+        match error {
+            Error::Deserialize { ref path, .. } => assert_eq!(path, "values[0].is_private"),
+            ref other => panic!("Expected deserialize error, got {:?}", other),
+        }
+        assert!(error
+            .to_string()
+            .contains("at values[0].is_private: invalid type"));
+    }
+
+    #[test]
+    fn pagelen_replaces_default() {
+        let api = Api::new("tester", "secret");
+        let query = api.repositories("team");
+        assert_eq!(
+            query.pagelen(100).get_query().get_url(""),
+            "repositories/team?pagelen=100"
+        );
+        assert_eq!(
+            query.limit(200).get_query().get_url(""),
+            "repositories/team?pagelen=75"
+        );
+    }
+
+    #[test]
+    fn limit_stops_fetching_pages() {
+        let transport = MemoryTransport::new();
+        let user = String::from_utf8_lossy(USER_JSON);
+        let body = format!(
+            r#"{{"values":[{0},{0},{0}],"next":"https://api.bitbucket.org/2.0/teams/team/members?pagelen=3&page=2"}}"#,
+            user
+        );
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members?pagelen=3",
+            Response::new(200, body.as_bytes()),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        let members = api.team("team").members();
+
+        assert_eq!(api.get(&members.limit(3)).unwrap().len(), 3);
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn pages_are_fetched_on_demand() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members",
+            Response::new(
+                200,
+                br#"{"values":[{"username":"a","nickname":"a","display_name":"A","uuid":"{a}","links":{}}],
+                    "next":"https://api.bitbucket.org/2.0/teams/team/members?page=2"}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+
+        let first = api.iter(&api.team("team").members()).next();
+
+        assert_eq!(first.unwrap().unwrap().username, "a");
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn error_responses_are_reported() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/users/nobody",
+            Response::new(
+                404,
+                br#"{"type": "error", "error": {"message": "nobody not found"}}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        match api.get(&api.user("nobody")) {
+            Err(Error::Http(e)) => {
+                assert_eq!(e.status, 404);
+                assert_eq!(e.message, "nobody not found");
+            }
+            other => panic!("Expected HTTP error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn transient_failures_are_retried() {
+        let transport = MemoryTransport::new();
+        let url = "https://api.bitbucket.org/2.0/teams/team/members?page=2";
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members",
+            Response::new(200, br#"{"values":[],"next":"https://api.bitbucket.org/2.0/teams/team/members?page=2"}"#),
+        );
+        transport
+            .respond(Method::Get, url, Response::new(503, b"Service Unavailable"))
+            .respond(
+                Method::Get,
+                url,
+                Response::new(429, b"").with_header("Retry-After", "0"),
+            )
+            .respond(Method::Get, url, Response::new(200, br#"{"values":[]}"#));
+        let api = Api::builder("tester", "secret")
+            .transport(transport.clone())
+            .retry_policy(
+                RetryPolicy::new().backoff(Duration::from_millis(0), Duration::from_millis(0)),
+            )
+            .build();
+
+        assert!(api.get(&api.team("team").members()).is_ok());
+        assert_eq!(transport.requests().len(), 4);
+    }
+
+    #[test]
+    fn retries_are_limited() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(502, b"Bad Gateway"),
+        );
+        let api = Api::builder("tester", "secret")
+            .transport(transport.clone())
+            .retry_policy(
+                RetryPolicy::new()
+                    .max_attempts(2)
+                    .backoff(Duration::from_millis(0), Duration::from_millis(0)),
+            )
+            .build();
+
+        let error = api.get(&api.user("tester")).unwrap_err();
+        assert_eq!(error.status(), Some(502));
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn long_retry_after_is_not_waited_for() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(429, b"").with_header("Retry-After", "3600"),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+
+        let error = api.get(&api.user("tester")).unwrap_err();
+        assert_eq!(error.status(), Some(429));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[derive(Debug, Serialize)]
+    struct Rename {
+        display_name: String,
+    }
+
+    impl PostQueryBuilder for Rename {
+        type Body = Rename;
+        type Item = User;
+        fn post_query(&self) -> BitBucketQuery {
+            BitBucketQuery::new("users/tester".to_owned())
+        }
+        fn post_body(&self) -> Option<&Rename> {
+            Some(self)
+        }
+    }
+
+    impl DeleteQueryBuilder for Rename {
+        fn delete_query(&self) -> BitBucketQuery {
+            BitBucketQuery::new("users/tester".to_owned())
+        }
+    }
+
+    #[test]
+    fn post_sends_json_body() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Post,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(201, USER_JSON),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        let query = Rename {
+            display_name: "Tester".to_owned(),
+        };
+
+        let user = api.post(&query).unwrap();
+
+        assert_eq!(user.display_name, "Tester");
+        let request = &transport.requests()[0];
+        assert_eq!(request.header("Content-Type"), Some("application/json"));
+        assert_eq!(request.body, Some(br#"{"display_name":"Tester"}"#.to_vec()));
+    }
+
+    #[test]
+    fn delete_accepts_empty_responses() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Delete,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(204, b""),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+        let query = Rename {
+            display_name: "Tester".to_owned(),
+        };
+
+        assert!(api.delete(&query).is_ok());
+    }
+
+    #[test]
+    fn rate_limit_headers_are_exposed() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(200, USER_JSON).with_header("X-RateLimit-Remaining", "42"),
+        );
+        let api = Api::builder("tester", "secret")
+            .transport(transport)
+            .rate_limiter(RateLimiter::per_second(100))
+            .build();
+        let clone = api.clone();
+
+        assert_eq!(api.rate_limit_status(), None);
+        api.get(&api.user("tester")).unwrap();
+
+        let status = clone.rate_limit_status().unwrap();
+        assert_eq!(status.remaining, Some(42));
+    }
+
+    #[test]
+    fn unsuccessful_status_is_an_error() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(401, b"Unauthorized"),
+        );
+        let api = Api::with_transport("tester", "wrong", transport);
+
+        let error = api.get(&api.user("tester")).unwrap_err();
+        assert_eq!(error.status(), Some(401));
+    }
+
+    #[test]
+    fn base_url_is_configurable() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "http://localhost:8080/2.0/teams/team/members",
+            Response::new(
+                200,
+                br#"{"values":[],"next":"https://api.bitbucket.org/2.0/teams/team/members?page=2"}"#,
+            ),
+        );
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members?page=2",
+            Response::new(200, br#"{"values":[]}"#),
+        );
+        let api = Api::builder("tester", "secret")
+            .base_url("http://localhost:8080/2.0")
+            .transport(transport.clone())
+            .build();
+
+        api.get(&api.team("team").members()).unwrap();
+
+        let urls: Vec<String> = transport.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "http://localhost:8080/2.0/teams/team/members",
+                "https://api.bitbucket.org/2.0/teams/team/members?page=2",
+            ]
+        );
+    }
 }
@@ -0,0 +1,396 @@
+//! The async_api module provides a futures based interface to BitBucket,
+//! available with the `async` cargo feature.
+//!
+//! `AsyncApi` uses the same query builders as `Api`. Requests are executed on a bounded pool
+//! of worker threads through the transport of the wrapped `Api`, so the returned futures and
+//! streams don't depend on a specific runtime. As many requests as there are workers are in
+//! flight at the same time, further requests wait for a free worker.
+//! Retries and rate limiting wait on the workers as well.
+
+use futures::channel::oneshot;
+use futures::future::{self, BoxFuture, FutureExt};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use serde;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use api::{
+    Api, BitBucketQuery, DeleteQueryBuilder, GetQueryBuilder, Page, PostQueryBuilder,
+    PutQueryBuilder, RawQueryBuilder,
+};
+use error::Error;
+use pullrequests::PullrequestsBuilder;
+use repositories::RepositoriesBuilder;
+use serde_json;
+use teams::TeamsBuilder;
+use transport::Method;
+use users::UsersBuilder;
+
+/// AsyncApi is the asynchronous counterpart to `Api`, returning futures and streams.
+/// ```
+/// extern crate futures;
+/// # extern crate bitbucket_api;
+/// use bitbucket_api::api::Api;
+/// use bitbucket_api::async_api::AsyncApi;
+/// use futures::executor::block_on;
+/// use futures::future::join_all;
+///
+/// # use bitbucket_api::test_utils;
+/// # fn main() {
+/// # let env = test_utils::get_test_env();
+/// let api = AsyncApi::new(Api::new(&env.user, &env.api_key));
+/// let repositories = api.repositories(&env.team);
+///
+/// let pullrequests = join_all(vec![
+///     api.get(&repositories.repo_slug(&env.repo).pullrequests()),
+///     api.get(&api.pullrequests(&env.user)),
+/// ]);
+/// for result in block_on(pullrequests) {
+///     assert!(result.is_ok());
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AsyncApi {
+    api: Api,
+    pool: WorkerPool,
+}
+
+/// Number of worker threads used by `AsyncApi::new`
+pub const DEFAULT_WORKERS: usize = 8;
+
+impl AsyncApi {
+    /// Get a new AsyncApi sending requests with the configuration of the given Api,
+    /// using `DEFAULT_WORKERS` worker threads.
+    pub fn new(api: Api) -> AsyncApi {
+        AsyncApi::with_workers(api, DEFAULT_WORKERS)
+    }
+
+    /// Get a new AsyncApi sending at most `workers` requests at the same time.
+    /// Clones share the same workers, which stop once all clones are dropped.
+    pub fn with_workers(api: Api, workers: usize) -> AsyncApi {
+        AsyncApi {
+            api,
+            pool: WorkerPool::new(workers),
+        }
+    }
+    /// The wrapped blocking Api
+    pub fn api(&self) -> &Api {
+        &self.api
+    }
+    /// Operations on Repositories Endpoint
+    pub fn repositories(&self, username: &str) -> RepositoriesBuilder {
+        self.api.repositories(username)
+    }
+    /// Operations on Pullrequests Endpoint
+    pub fn pullrequests(&self, username: &str) -> PullrequestsBuilder {
+        self.api.pullrequests(username)
+    }
+    /// Operations on User Endpoint
+    pub fn user(&self, username: &str) -> UsersBuilder {
+        self.api.user(username)
+    }
+    /// Operations on Team Endpoint
+    pub fn team(&self, teamname: &str) -> TeamsBuilder {
+        self.api.team(teamname)
+    }
+
+    /// Resolves to all items returned by a query, see `Api::get`.
+    pub fn get<T>(
+        &self,
+        query: &dyn GetQueryBuilder<Item = T>,
+    ) -> BoxFuture<'static, Result<Vec<T>, Error>>
+    where
+        T: serde::de::DeserializeOwned + fmt::Debug + Send + 'static,
+    {
+        let api = self.api.clone();
+        let query = query.get_query();
+        self.pool.run(move || api.iter_query(query).collect())
+    }
+
+    /// Resolves to a single page of a paged query, see `Api::get_page`.
+    pub fn get_page<T>(
+        &self,
+        query: &dyn GetQueryBuilder<Item = T>,
+        page_number: usize,
+    ) -> BoxFuture<'static, Result<Page<T>, Error>>
+    where
+        T: serde::de::DeserializeOwned + fmt::Debug + Send + 'static,
+    {
+        let api = self.api.clone();
+        let query = query
+            .get_query()
+            .with_param("page", &page_number.to_string());
+        self.pool.run(move || api.fetch_page(&query, false))
+    }
+
+    /// Streams all items returned by a query, fetching further pages on demand,
+    /// see `Api::iter`. The stream ends after the first error.
+    pub fn stream<T>(
+        &self,
+        query: &dyn GetQueryBuilder<Item = T>,
+    ) -> BoxStream<'static, Result<T, Error>>
+    where
+        T: serde::de::DeserializeOwned + fmt::Debug + Send + 'static,
+    {
+        let api = self.api.clone();
+        let pool = self.pool.clone();
+        let query = query.get_query();
+        let limit = query.limit();
+        let pages = stream::unfold(Some((query, true)), move |state| {
+            let api = api.clone();
+            match state {
+                None => future::ready(None).left_future(),
+                Some((query, first_page)) => pool
+                    .run(move || api.fetch_page(&query, first_page))
+                    .map(|result| match result {
+                        Ok(page) => {
+                            let next = page.next.map(|url| (BitBucketQuery::new(url), false));
+                            Some((Ok(page.values), next))
+                        }
+                        Err(e) => Some((Err(e), None)),
+                    })
+                    .right_future(),
+            }
+        });
+        let items = pages
+            .map_ok(|values| stream::iter(values.into_iter().map(Ok)))
+            .try_flatten();
+        match limit {
+            Some(limit) => items.take(limit).boxed(),
+            None => items.boxed(),
+        }
+    }
+
+    /// Resolves to a plain text resource, see `Api::get_raw`.
+    pub fn get_raw(
+        &self,
+        query: &dyn RawQueryBuilder,
+    ) -> BoxFuture<'static, Result<String, Error>> {
+        let api = self.api.clone();
+        let query = query.raw_query();
+        self.pool.run(move || api.send_raw(&query))
+    }
+
+    /// Resolves to BitBucket's answer to a POST request, see `Api::post`.
+    pub fn post<B, T>(
+        &self,
+        query: &dyn PostQueryBuilder<Body = B, Item = T>,
+    ) -> BoxFuture<'static, Result<T, Error>>
+    where
+        B: serde::Serialize,
+        T: serde::de::DeserializeOwned + fmt::Debug + Send + 'static,
+    {
+        self.send_json(Method::Post, query.post_query(), query.post_body())
+    }
+
+    /// Resolves to BitBucket's answer to a PUT request, see `Api::put`.
+    pub fn put<B, T>(
+        &self,
+        query: &dyn PutQueryBuilder<Body = B, Item = T>,
+    ) -> BoxFuture<'static, Result<T, Error>>
+    where
+        B: serde::Serialize,
+        T: serde::de::DeserializeOwned + fmt::Debug + Send + 'static,
+    {
+        self.send_json(Method::Put, query.put_query(), Some(query.put_body()))
+    }
+
+    /// Resolves once the object is removed, see `Api::delete`.
+    pub fn delete(&self, query: &dyn DeleteQueryBuilder) -> BoxFuture<'static, Result<(), Error>> {
+        let api = self.api.clone();
+        let query = query.delete_query();
+        self.pool.run(move || {
+            api.send_json_bytes(Method::Delete, &query, None)
+                .map(|_: serde_json::Value| ())
+        })
+    }
+
+    fn send_json<B, T>(
+        &self,
+        method: Method,
+        query: BitBucketQuery,
+        body: Option<&B>,
+    ) -> BoxFuture<'static, Result<T, Error>>
+    where
+        B: serde::Serialize,
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        // The body is serialized up front, as it is only borrowed from the query
+        let body = match body.map(serde_json::to_vec) {
+            Some(Ok(body)) => Some(body),
+            Some(Err(e)) => return future::ready(Err(Error::Serialize(e))).boxed(),
+            None => None,
+        };
+        let api = self.api.clone();
+        self.pool
+            .run(move || api.send_json_bytes(method, &query, body))
+    }
+}
+
+impl From<Api> for AsyncApi {
+    fn from(api: Api) -> AsyncApi {
+        AsyncApi::new(api)
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Fixed number of threads running blocking requests, shared by all clones
+#[derive(Debug, Clone)]
+struct WorkerPool {
+    sender: Arc<Mutex<mpsc::Sender<Job>>>,
+}
+
+impl WorkerPool {
+    fn new(workers: usize) -> WorkerPool {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..workers.max(1) {
+            let receiver = receiver.clone();
+            thread::spawn(move || loop {
+                // The lock is only held while waiting for the next job
+                let job = receiver.lock().expect("WorkerPool lock poisoned").recv();
+                match job {
+                    // A panicking job drops its result sender, failing only its own future
+                    Ok(job) => {
+                        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                    }
+                    // All pool handles are gone
+                    Err(_) => break,
+                }
+            });
+        }
+        WorkerPool {
+            sender: Arc::new(Mutex::new(sender)),
+        }
+    }
+
+    /// Runs a blocking function on the next free worker, resolving to its result.
+    fn run<F, R>(&self, f: F) -> BoxFuture<'static, R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            // The receiver is gone if the future was dropped, the result is not needed anymore
+            let _ = sender.send(f());
+        });
+        self.sender
+            .lock()
+            .expect("WorkerPool lock poisoned")
+            .send(job)
+            .expect("WorkerPool workers stopped");
+        receiver
+            .map(|result| result.expect("BitBucket request panicked"))
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::future::join_all;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use transport::{HttpTransport, MemoryTransport, Method, Request, Response, TransportError};
+
+    /// Transport recording how many requests are sent at the same time
+    #[derive(Debug, Default)]
+    struct CountingTransport {
+        inner: MemoryTransport,
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    impl HttpTransport for CountingTransport {
+        fn send(&self, request: &Request) -> Result<Response, TransportError> {
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(current, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(10));
+            self.current.fetch_sub(1, Ordering::SeqCst);
+            self.inner.send(request)
+        }
+    }
+
+    fn members_api() -> (AsyncApi, MemoryTransport) {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members",
+            Response::new(
+                200,
+                br#"{"values":[{"username":"a","nickname":"a","display_name":"A","uuid":"{a}","links":{}}],
+                    "next":"https://api.bitbucket.org/2.0/teams/team/members?page=2"}"#,
+            ),
+        );
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/teams/team/members?page=2",
+            Response::new(
+                200,
+                br#"{"values":[{"username":"b","nickname":"b","display_name":"B","uuid":"{b}","links":{}}]}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        (AsyncApi::new(api), transport)
+    }
+
+    #[test]
+    fn get_resolves_all_pages() {
+        let (api, _) = members_api();
+        let members = block_on(api.get(&api.team("team").members())).unwrap();
+        assert_eq!(members.len(), 2);
+    }
+
+    #[test]
+    fn stream_fetches_pages_on_demand() {
+        let (api, transport) = members_api();
+        let mut members = api.stream(&api.team("team").members());
+
+        let first = block_on(members.next()).unwrap().unwrap();
+
+        assert_eq!(first.username, "a");
+        assert_eq!(transport.requests().len(), 1);
+
+        let rest: Vec<_> = block_on(members.collect::<Vec<_>>());
+        assert_eq!(rest.len(), 1);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn requests_are_limited_to_the_workers() {
+        let transport = Arc::new(CountingTransport::default());
+        transport.inner.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/users/tester",
+            Response::new(
+                200,
+                br#"{"username":"tester","nickname":"tester","display_name":"Tester","uuid":"{1}","links":{}}"#,
+            ),
+        );
+        let api = Api::builder("tester", "secret")
+            .transport(SharedTransport(transport.clone()))
+            .build();
+        let api = AsyncApi::with_workers(api, 2);
+
+        let users = block_on(join_all((0..10).map(|_| api.get(&api.user("tester")))));
+
+        assert!(users.iter().all(|users| users.is_ok()));
+        assert!(transport.max.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[derive(Debug)]
+    struct SharedTransport(Arc<CountingTransport>);
+
+    impl HttpTransport for SharedTransport {
+        fn send(&self, request: &Request) -> Result<Response, TransportError> {
+            self.0.send(request)
+        }
+    }
+}
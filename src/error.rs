@@ -0,0 +1,336 @@
+//! The error module defines the Error type returned by all fallible Api operations.
+
+use serde_json;
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+
+use transport::{Response, TransportError};
+
+/// Error returned by the Api, distinguishing the different ways a request can fail.
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or no response was received, e.g. network failures.
+    Transport(TransportError),
+    /// BitBucket answered the request with an unsuccessful status code
+    Http(Box<ApiError>),
+    /// The response did not match the expected data structure.
+    Deserialize {
+        /// URL of the request returning the unexpected response
+        url: String,
+        /// Path of the mismatching value, e.g. `values[0].is_private`, empty for the whole body
+        path: String,
+        /// Underlying serde error describing the mismatch
+        source: serde_json::Error,
+    },
+    /// The request body could not be serialized to JSON.
+    Serialize(serde_json::Error),
+    /// Following the pages of a paged response failed.
+    Pagination {
+        /// URL of the page which could not be processed
+        url: String,
+        /// Description of the problem
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Transport(ref e) => write!(f, "Transport error: {}", e),
+            Error::Http(ref e) => write!(f, "{}", e),
+            Error::Deserialize {
+                ref url,
+                ref path,
+                ref source,
+            } if !path.is_empty() => {
+                write!(
+                    f,
+                    "Unexpected response from {} at {}: {}",
+                    url, path, source
+                )
+            }
+            Error::Deserialize {
+                ref url,
+                ref source,
+                ..
+            } => write!(f, "Unexpected response from {}: {}", url, source),
+            Error::Serialize(ref e) => write!(f, "Invalid request body: {}", e),
+            Error::Pagination {
+                ref url,
+                ref message,
+            } => write!(f, "Pagination of {} failed: {}", url, message),
+        }
+    }
+}
+
+impl Error {
+    /// Builds the error for a response body not matching the expected data structure,
+    /// locating the mismatching value by the position serde reported.
+    pub(crate) fn deserialize(url: &str, body: &[u8], source: serde_json::Error) -> Error {
+        let path = json_path(body, source.line(), source.column());
+        Error::Deserialize {
+            url: url.to_owned(),
+            path,
+            source,
+        }
+    }
+
+    /// Returns the HTTP status code if BitBucket answered with an error.
+    pub fn status(&self) -> Option<u32> {
+        match *self {
+            Error::Http(ref e) => Some(e.status),
+            _ => None,
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Transport(ref e) => Some(&**e),
+            Error::Http(ref e) => Some(&**e),
+            Error::Deserialize { ref source, .. } => Some(source),
+            Error::Serialize(ref e) => Some(e),
+            Error::Pagination { .. } => None,
+        }
+    }
+}
+
+impl From<TransportError> for Error {
+    fn from(e: TransportError) -> Error {
+        Error::Transport(e)
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(e: ApiError) -> Error {
+        Error::Http(Box::new(e))
+    }
+}
+
+/// Error reported by BitBucket, e.g. an unknown repository or missing permissions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    /// HTTP status code of the response
+    pub status: u32,
+    /// URL of the failed request
+    pub url: String,
+    /// Error message provided by BitBucket, or the raw response body if it was not
+    /// in BitBucket's error format
+    pub message: String,
+    /// Optional details on the error provided by BitBucket
+    pub detail: Option<String>,
+    /// Validation errors per request field, e.g. when creating an object failed
+    pub fields: HashMap<String, Vec<String>>,
+}
+
+impl ApiError {
+    /// Builds the error for an unsuccessful response, parsing BitBucket's standard error format
+    /// `{"type": "error", "error": {"message": ..., "detail": ..., "fields": ...}}`
+    /// if possible.
+    pub(crate) fn from_response(url: &str, response: &Response) -> ApiError {
+        match serde_json::from_slice::<ErrorResponse>(&response.body) {
+            Ok(ref body) if body.bb_type == "error" => ApiError {
+                status: response.status,
+                url: url.to_owned(),
+                message: body.error.message.to_owned(),
+                detail: body.error.detail.to_owned(),
+                fields: body.error.fields.to_owned(),
+            },
+            _ => {
+                let body = String::from_utf8_lossy(&response.body);
+                let message = if body.trim().is_empty() {
+                    format!("Request failed with status {}", response.status)
+                } else {
+                    body.trim().to_owned()
+                };
+                ApiError {
+                    status: response.status,
+                    url: url.to_owned(),
+                    message,
+                    detail: None,
+                    fields: HashMap::new(),
+                }
+            }
+        }
+    }
+
+    /// Returns true if the requested object does not exist, or is not visible to the user.
+    pub fn is_not_found(&self) -> bool {
+        self.status == 404
+    }
+
+    /// Returns true if the credentials were rejected or lack the required permissions.
+    pub fn is_unauthorized(&self) -> bool {
+        self.status == 401 || self.status == 403
+    }
+
+    /// Returns true if the request was rejected due to rate limiting.
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == 429
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "HTTP {} from {}: {}",
+            self.status, self.url, self.message
+        )?;
+        if let Some(ref detail) = self.detail {
+            write!(f, " ({})", detail)?;
+        }
+        for (field, errors) in &self.fields {
+            write!(f, ", {}: {}", field, errors.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ApiError {}
+
+/// Object or array enclosing a position in a JSON document
+enum Frame {
+    Object {
+        key: Option<String>,
+        expect_key: bool,
+    },
+    Array {
+        index: usize,
+    },
+}
+
+/// Path of the JSON value parsed last when serde_json stopped at the given line and column,
+/// which point at the next unparsed byte, e.g. `values[0].is_private`
+fn json_path(body: &[u8], line: usize, column: usize) -> String {
+    let offset = body
+        .split(|&byte| byte == b'\n')
+        .take(line.saturating_sub(1))
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        + column.saturating_sub(1);
+    let body = &body[..offset.min(body.len())];
+
+    let mut frames = Vec::new();
+    let mut i = 0;
+    while i < body.len() {
+        match body[i] {
+            b'{' => frames.push(Frame::Object {
+                key: None,
+                expect_key: true,
+            }),
+            b'[' => frames.push(Frame::Array { index: 0 }),
+            b'}' | b']' => {
+                frames.pop();
+            }
+            b',' => match frames.last_mut() {
+                Some(&mut Frame::Object {
+                    ref mut expect_key, ..
+                }) => *expect_key = true,
+                Some(&mut Frame::Array { ref mut index }) => *index += 1,
+                None => {}
+            },
+            b'"' => {
+                let start = i + 1;
+                i += 1;
+                while i < body.len() && body[i] != b'"' {
+                    if body[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                if let Some(&mut Frame::Object {
+                    ref mut key,
+                    ref mut expect_key,
+                }) = frames.last_mut()
+                {
+                    if *expect_key {
+                        let end = i.min(body.len());
+                        *key = Some(String::from_utf8_lossy(&body[start..end]).into_owned());
+                        *expect_key = false;
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let mut path = String::new();
+    for frame in &frames {
+        match *frame {
+            Frame::Object {
+                key: Some(ref key), ..
+            } => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+            }
+            Frame::Object { key: None, .. } => {}
+            Frame::Array { index } => path.push_str(&format!("[{}]", index)),
+        }
+    }
+    path
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    #[serde(rename = "type")]
+    bb_type: String,
+    error: ErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    message: String,
+    detail: Option<String>,
+    #[serde(default)]
+    fields: HashMap<String, Vec<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_mismatching_values() {
+        let body = br#"{"values": [{"id": 1}, {"id": 2, "links": {"html": {"href": 3}}}]}"#;
+        let href = body.iter().position(|&byte| byte == b'3').unwrap();
+        assert_eq!(json_path(body, 1, href + 2), "values[1].links.html.href");
+        assert_eq!(json_path(b"{\"a\":\n[1, 2]}", 2, 6), "a[1]");
+        assert_eq!(json_path(br#"{"values": [{"id": 1}]"#, 1, 22), "values[0]");
+    }
+
+    #[test]
+    fn parses_bitbucket_error_body() {
+        let response = Response::new(
+            400,
+            br#"{"type": "error", "error": {"message": "Bad request", "detail": "invalid",
+                 "fields": {"source": ["source branch does not exist"]}}}"#,
+        );
+        let error = ApiError::from_response("http://localhost/x", &response);
+
+        assert_eq!(error.status, 400);
+        assert_eq!(error.message, "Bad request");
+        assert_eq!(error.detail, Some("invalid".to_owned()));
+        assert_eq!(
+            error.fields["source"],
+            vec!["source branch does not exist".to_owned()]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_raw_body() {
+        let response = Response::new(502, b"<html>Bad Gateway</html>\n");
+        let error = ApiError::from_response("http://localhost/x", &response);
+        assert_eq!(error.message, "<html>Bad Gateway</html>");
+
+        let response = Response::new(403, b"");
+        let error = ApiError::from_response("http://localhost/x", &response);
+        assert_eq!(error.message, "Request failed with status 403");
+        assert!(error.is_unauthorized());
+    }
+}
@@ -24,9 +24,18 @@
  * Optional, Make Link Retrieval Function new Type Links = HashMap<String,Link>
 */
 
+extern crate base64;
+#[cfg(feature = "chrono")]
+extern crate chrono;
 extern crate curl;
+#[cfg(feature = "async")]
+extern crate futures;
 extern crate serde;
 extern crate serde_json;
+#[cfg(feature = "yaml")]
+extern crate serde_yaml;
+#[cfg(feature = "toml")]
+extern crate toml;
 extern crate url;
 #[macro_use]
 extern crate log;
@@ -39,11 +48,21 @@ extern crate serde_derive;
 extern crate lazy_static;
 
 pub mod api;
+#[cfg(feature = "async")]
+pub mod async_api;
+pub mod error;
+pub mod policy;
 mod pullrequests;
+pub mod query;
+pub mod ratelimit;
 mod repositories;
+pub mod retry;
 mod teams;
+pub mod transport;
 mod users;
 
+pub use error::Error;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,7 +74,7 @@ mod tests {
             env_logger::init();
             get_test_env()
         };
-        static ref API: api::Api = { api::Api::new(&ENV.user, &ENV.api_key) };
+        static ref API: api::Api = api::Api::new(&ENV.user, &ENV.api_key);
     }
 
     #[test]
@@ -144,7 +163,7 @@ mod tests {
     fn get_members() {
         let members = API.team(&ENV.team).members();
         let team_members: Vec<users::User> = API.get(&members).expect("Get Team Members");
-        assert!(team_members.len() > 0);
+        assert!(!team_members.is_empty());
     }
 }
 
@@ -173,6 +192,6 @@ pub mod test_utils {
     }
 
     fn _get_test_env_var(key: &str) -> String {
-        env::var(key).expect(&format!("Missing ENV {}", key)).into()
+        env::var(key).unwrap_or_else(|_| panic!("Missing ENV {}", key))
     }
 }
@@ -0,0 +1,462 @@
+//! The policy module keeps the branch restrictions of repositories in sync with a declared
+//! `Policy`, e.g. loaded from a TOML or YAML file shared by many repositories.
+//!
+//! Syncing compares the policy to the live restrictions, resulting in a `Plan` of creations,
+//! updates and deletions, which is applied unless running in `SyncMode::DryRun`.
+//! Applying a policy twice results in an empty plan the second time.
+//! ```
+//! use bitbucket_api::api::{Api, BranchRestrictionKind, BranchRestrictionSpec};
+//! use bitbucket_api::policy::{Policy, SyncMode};
+//!
+//! # use bitbucket_api::test_utils;
+//! # let env = test_utils::get_test_env();
+//! let api = Api::new(&env.user, &env.api_key);
+//! let policy = Policy::new(vec![
+//!     BranchRestrictionSpec::new(BranchRestrictionKind::Force).pattern("master"),
+//!     BranchRestrictionSpec::new(BranchRestrictionKind::RequireApprovalsToMerge)
+//!         .pattern("master")
+//!         .value(1),
+//! ]);
+//! let restrictions = api.repositories(&env.team).repo_slug(&env.repo).branch_restrictions();
+//!
+//! let plan = policy.sync(&api, &restrictions, SyncMode::DryRun).unwrap();
+//! println!("{}", plan);
+//! ```
+
+use std::fmt;
+
+use api::{Api, BranchMatchKind, BranchPermission, BranchRestrictionSpec};
+use error::Error;
+use repositories::branchrestrictions::BranchRestrictionBuilder;
+use serde_json;
+#[cfg(feature = "yaml")]
+use serde_yaml;
+#[cfg(feature = "toml")]
+use toml;
+
+/// The branch restrictions a repository should have, no more and no less.
+///
+/// In TOML, each restriction is declared as `[[restrictions]]` table, see
+/// `BranchRestrictionSpec` for the available keys, e.g.
+/// ```toml
+/// [[restrictions]]
+/// kind = "require_approvals_to_merge"
+/// pattern = "master"
+/// value = 2
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    /// The desired restrictions
+    #[serde(default)]
+    pub restrictions: Vec<BranchRestrictionSpec>,
+}
+
+/// Whether `Policy::sync` changes the restrictions or only reports the changes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SyncMode {
+    /// Only compute the plan
+    DryRun,
+    /// Compute and apply the plan
+    Apply,
+}
+
+impl Policy {
+    /// Get a policy consisting of the given restrictions
+    pub fn new(restrictions: Vec<BranchRestrictionSpec>) -> Policy {
+        Policy { restrictions }
+    }
+
+    /// Reads a policy from JSON
+    pub fn from_json(json: &str) -> Result<Policy, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Reads a policy from TOML, available with the `toml` feature
+    #[cfg(feature = "toml")]
+    pub fn from_toml(policy: &str) -> Result<Policy, toml::de::Error> {
+        toml::from_str(policy)
+    }
+
+    /// Reads a policy from YAML, available with the `yaml` feature
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(policy: &str) -> Result<Policy, serde_yaml::Error> {
+        serde_yaml::from_str(policy)
+    }
+
+    /// Compares the policy to the existing restrictions.
+    ///
+    /// Restrictions are matched by kind and affected branches. Matching restrictions
+    /// differing in users, groups or value are updated, the others created or deleted.
+    pub fn plan(&self, existing: &[BranchPermission]) -> Plan {
+        let mut remaining: Vec<(u64, BranchRestrictionSpec)> = existing
+            .iter()
+            .map(|restriction| (restriction.id, normalize(restriction.spec())))
+            .collect();
+        let mut changes = Vec::new();
+        for desired in &self.restrictions {
+            let desired = normalize(desired.clone());
+            match remaining
+                .iter()
+                .position(|entry| same_target(&entry.1, &desired))
+            {
+                Some(index) => {
+                    let (id, live) = remaining.remove(index);
+                    if live != desired {
+                        changes.push(Change::Update {
+                            id,
+                            from: live,
+                            to: desired,
+                        });
+                    }
+                }
+                None => changes.push(Change::Create(desired)),
+            }
+        }
+        changes.extend(
+            remaining
+                .into_iter()
+                .map(|(id, spec)| Change::Delete { id, spec }),
+        );
+        Plan { changes }
+    }
+
+    /// Fetches the live restrictions and plans the changes required to match the policy,
+    /// applying them in `SyncMode::Apply`. Returns the plan, even if it was not applied.
+    pub fn sync(
+        &self,
+        api: &Api,
+        restrictions: &BranchRestrictionBuilder,
+        mode: SyncMode,
+    ) -> Result<Plan, Error> {
+        let plan = self.plan(&api.get(restrictions)?);
+        if mode == SyncMode::Apply {
+            plan.apply(api, restrictions)?;
+        }
+        Ok(plan)
+    }
+}
+
+/// A single change required to match a Policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Add a missing restriction
+    Create(BranchRestrictionSpec),
+    /// Replace the users, groups or value of the restriction with the given id
+    #[allow(missing_docs)]
+    Update {
+        id: u64,
+        from: BranchRestrictionSpec,
+        to: BranchRestrictionSpec,
+    },
+    /// Remove a restriction not part of the policy
+    #[allow(missing_docs)]
+    Delete {
+        id: u64,
+        spec: BranchRestrictionSpec,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Change::Create(ref spec) => write!(f, "+ create {}", Summary(spec)),
+            Change::Update {
+                id,
+                ref from,
+                ref to,
+            } => write!(f, "~ update #{} {} => {}", id, Summary(from), Summary(to)),
+            Change::Delete { id, ref spec } => write!(f, "- delete #{} {}", id, Summary(spec)),
+        }
+    }
+}
+
+/// The changes required to match a Policy, printed one change per line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// Changes in the order they are applied
+    pub changes: Vec<Change>,
+}
+
+impl Plan {
+    /// Whether the restrictions already match the policy
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Applies the changes, creating and updating restrictions before deleting any,
+    /// so branches are never left unprotected in between. Stops at the first error.
+    pub fn apply(&self, api: &Api, restrictions: &BranchRestrictionBuilder) -> Result<(), Error> {
+        for change in &self.changes {
+            match *change {
+                Change::Create(ref spec) => {
+                    api.post(&restrictions.create(spec))?;
+                }
+                Change::Update { id, ref to, .. } => {
+                    api.put(&restrictions.id(id).update(to))?;
+                }
+                Change::Delete { .. } => {}
+            }
+            info!("Applied {}", change);
+        }
+        for change in &self.changes {
+            if let Change::Delete { id, .. } = *change {
+                api.delete(&restrictions.id(id))?;
+                info!("Applied {}", change);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "No changes, restrictions match the policy");
+        }
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Human readable form of a restriction, e.g. `require_approvals_to_merge on "master" (value 2)`
+struct Summary<'a>(&'a BranchRestrictionSpec);
+
+impl<'a> fmt::Display for Summary<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let spec = self.0;
+        write!(f, "{} on ", spec.kind)?;
+        match (spec.branch_match_kind, &spec.pattern, &spec.branch_type) {
+            (BranchMatchKind::BranchingModel, _, Some(branch_type)) => {
+                write!(f, "{} branches", branch_type)?
+            }
+            (_, Some(pattern), _) => write!(f, "{:?}", pattern)?,
+            _ => write!(f, "all branches")?,
+        }
+        if let Some(value) = spec.value {
+            write!(f, " (value {})", value)?;
+        }
+        for user in &spec.users {
+            write!(f, " +user {}", user.uuid)?;
+        }
+        for group in &spec.groups {
+            write!(f, " +group {}", group.slug)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether both restrictions are of the same kind and affect the same branches
+fn same_target(a: &BranchRestrictionSpec, b: &BranchRestrictionSpec) -> bool {
+    a.kind == b.kind
+        && a.branch_match_kind == b.branch_match_kind
+        && match a.branch_match_kind {
+            BranchMatchKind::Glob => a.pattern == b.pattern,
+            BranchMatchKind::BranchingModel => a.branch_type == b.branch_type,
+        }
+}
+
+/// Orders users and groups, which BitBucket returns in any order
+fn normalize(mut spec: BranchRestrictionSpec) -> BranchRestrictionSpec {
+    spec.users.sort();
+    spec.users.dedup();
+    spec.groups.sort();
+    spec.groups.dedup();
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::{BranchRestrictionKind, BranchType};
+    use transport::{MemoryTransport, Method, Response};
+
+    const RESTRICTIONS_URL: &str =
+        "https://api.bitbucket.org/2.0/repositories/team/repo/branch-restrictions";
+
+    fn live_restrictions() -> Vec<BranchPermission> {
+        serde_json::from_str(
+            r#"[
+                {"type": "branchrestriction", "id": 1, "kind": "force", "branch_match_kind": "glob",
+                 "pattern": "master", "users": [], "groups": [], "value": null, "links": {}},
+                {"type": "branchrestriction", "id": 2, "kind": "require_approvals_to_merge",
+                 "branch_match_kind": "glob", "pattern": "master", "users": [], "groups": [],
+                 "value": 1, "links": {}},
+                {"type": "branchrestriction", "id": 3, "kind": "delete",
+                 "branch_match_kind": "branching_model", "branch_type": "release", "pattern": "",
+                 "users": [], "groups": [], "value": null, "links": {}}
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    fn policy() -> Policy {
+        Policy::new(vec![
+            BranchRestrictionSpec::new(BranchRestrictionKind::Force).pattern("master"),
+            BranchRestrictionSpec::new(BranchRestrictionKind::RequireApprovalsToMerge)
+                .pattern("master")
+                .value(2),
+            BranchRestrictionSpec::new(BranchRestrictionKind::Push)
+                .branch_type(BranchType::Production)
+                .group_slug("release-managers"),
+        ])
+    }
+
+    #[test]
+    fn plans_creates_updates_and_deletes() {
+        let plan = policy().plan(&live_restrictions());
+
+        assert_eq!(plan.changes.len(), 3);
+        match plan.changes[0] {
+            Change::Update { id, ref to, .. } => {
+                assert_eq!(id, 2);
+                assert_eq!(to.value, Some(2));
+            }
+            ref other => panic!("expected update, got {}", other),
+        }
+        match plan.changes[1] {
+            Change::Create(ref spec) => assert_eq!(spec.kind, BranchRestrictionKind::Push),
+            ref other => panic!("expected create, got {}", other),
+        }
+        match plan.changes[2] {
+            Change::Delete { id, .. } => assert_eq!(id, 3),
+            ref other => panic!("expected delete, got {}", other),
+        }
+        assert_eq!(
+            plan.to_string(),
+            "~ update #2 require_approvals_to_merge on \"master\" (value 1) => require_approvals_to_merge on \"master\" (value 2)\n\
+             + create push on production branches +group release-managers\n\
+             - delete #3 delete on release branches\n"
+        );
+    }
+
+    #[test]
+    fn matching_restrictions_need_no_changes() {
+        let live = live_restrictions();
+        let policy = Policy::new(live.iter().map(BranchPermission::spec).collect());
+        assert!(policy.plan(&live).is_empty());
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let transport = MemoryTransport::new();
+        let live = r#"{"values": [{"type": "branchrestriction", "id": 3, "kind": "delete",
+            "branch_match_kind": "glob", "pattern": "*", "users": [], "groups": [], "links": {}}]}"#;
+        transport
+            .respond(
+                Method::Get,
+                RESTRICTIONS_URL,
+                Response::new(200, live.as_bytes()),
+            )
+            .respond(
+                Method::Delete,
+                &format!("{}/3", RESTRICTIONS_URL),
+                Response::new(204, b""),
+            );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        let restrictions = api
+            .repositories("team")
+            .repo_slug("repo")
+            .branch_restrictions();
+
+        let plan = Policy::default()
+            .sync(&api, &restrictions, SyncMode::DryRun)
+            .unwrap();
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(transport.requests().len(), 1);
+
+        Policy::default()
+            .sync(&api, &restrictions, SyncMode::Apply)
+            .unwrap();
+        assert_eq!(transport.requests()[2].method, Method::Delete);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn reads_toml() {
+        let parsed = Policy::from_toml(
+            r#"
+            [[restrictions]]
+            kind = "force"
+            pattern = "master"
+
+            [[restrictions]]
+            kind = "require_approvals_to_merge"
+            pattern = "master"
+            value = 2
+
+            [[restrictions]]
+            kind = "push"
+            branch_match_kind = "branching_model"
+            branch_type = "production"
+            groups = [{slug = "release-managers"}]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed, policy());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn declared_policy_matches_its_restrictions() {
+        let transport = MemoryTransport::new();
+        let live = r#"{"values": [
+            {"type": "branchrestriction", "id": 1, "kind": "delete", "branch_match_kind": "glob",
+             "pattern": "*", "users": [], "groups": [], "links": {}},
+            {"type": "branchrestriction", "id": 2, "kind": "push",
+             "branch_match_kind": "branching_model", "branch_type": "production", "pattern": "",
+             "users": [], "groups": [{"name": "Release Managers", "slug": "release-managers"}],
+             "links": {}}
+        ]}"#;
+        transport.respond(
+            Method::Get,
+            RESTRICTIONS_URL,
+            Response::new(200, live.as_bytes()),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        let restrictions = api
+            .repositories("team")
+            .repo_slug("repo")
+            .branch_restrictions();
+        let parsed = Policy::from_toml(
+            r#"
+            [[restrictions]]
+            kind = "delete"
+
+            [[restrictions]]
+            kind = "push"
+            branch_match_kind = "branching_model"
+            branch_type = "production"
+            groups = [{slug = "release-managers"}]
+            "#,
+        )
+        .unwrap();
+
+        let plan = parsed.sync(&api, &restrictions, SyncMode::Apply).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn reads_yaml() {
+        let parsed = Policy::from_yaml(
+            r#"
+restrictions:
+  - kind: force
+    pattern: master
+  - kind: require_approvals_to_merge
+    pattern: master
+    value: 2
+  - kind: push
+    branch_match_kind: branching_model
+    branch_type: production
+    groups:
+      - slug: release-managers
+"#,
+        )
+        .unwrap();
+        assert_eq!(parsed, policy());
+    }
+}
@@ -1,30 +1,68 @@
 use api;
+use query::{self, Direction, Query, Sort};
 
-use std::fmt;
-use serde_json;
+use serde::ser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Clone, Debug)]
 pub struct PullrequestsBuilder {
     username: String,
+    states: Vec<PullRequestState>,
+    query: Option<Query>,
+    sort: Option<Sort>,
 }
 
 impl PullrequestsBuilder {
     pub(crate) fn new(username: &str) -> PullrequestsBuilder {
         PullrequestsBuilder {
             username: username.to_owned(),
+            states: Vec::new(),
+            query: None,
+            sort: None,
         }
     }
+
+    /// Only returns Pullrequests in the given state, may be called multiple times.
+    /// BitBucket returns only open Pullrequests unless a state is given.
+    pub fn state(&mut self, state: PullRequestState) -> &mut PullrequestsBuilder {
+        self.states.push(state);
+        self
+    }
+
+    /// Only returns Pullrequests matching the query, see the query module
+    pub fn query(&mut self, query: Query) -> &mut PullrequestsBuilder {
+        self.query = Some(query);
+        self
+    }
+
+    /// Sorts the results by the given field, see the query module
+    pub fn sort(&mut self, field: &str, direction: Direction) -> &mut PullrequestsBuilder {
+        self.sort = Some(Sort::new(field, direction));
+        self
+    }
 }
 
 impl api::GetQueryBuilder for PullrequestsBuilder {
     type Item = PullRequest;
     fn get_query(&self) -> api::BitBucketQuery {
         let url_path = format!(r#"pullrequests/{}?pagelen=50"#, self.username);
-        api::BitBucketQuery::new(url_path)
+        let url_path = with_states(api::BitBucketQuery::new(url_path), &self.states);
+        query::apply(url_path, self.query.as_ref(), self.sort.as_ref())
     }
 }
 
+/// Adds a state parameter for each state, BitBucket returns Pullrequests in any of them.
+pub(crate) fn with_states(
+    query: api::BitBucketQuery,
+    states: &[PullRequestState],
+) -> api::BitBucketQuery {
+    states
+        .iter()
+        .fold(query, |query, state| query.add_param("state", state.as_str()))
+}
+
 /// BitBucket data structure representing a single Pullrequest
 #[derive(Debug, Clone, Deserialize)]
 #[allow(missing_docs)]
@@ -32,21 +70,30 @@ pub struct PullRequest {
     pub id: u64,
     pub title: String,
     pub state: PullRequestState,
-    // TODO structure for PR summary
-    pub summary: serde_json::Value,
+    #[serde(default)]
+    pub summary: api::Rendered,
+    #[serde(default)]
     pub description: String,
     pub author: api::User,
     pub close_source_branch: bool,
-    pub closed_by: serde_json::Value,
+    pub closed_by: Option<api::User>,
     pub comment_count: u32,
-    pub created_on: String,
+    pub created_on: api::Timestamp,
     pub destination: api::Sourctination,
     pub source: api::Sourctination,
+    #[serde(default)]
     pub links: HashMap<String, api::Link>,
-    pub merge_commit: serde_json::Value,
+    pub merge_commit: Option<api::Commit>,
     pub reason: String,
     pub task_count: u32,
-    pub updated_on: String,
+    pub updated_on: api::Timestamp,
+    /// Users who reviewed, commented on or were requested to review the Pullrequest.
+    /// Only included when fetching a single Pullrequest.
+    #[serde(default)]
+    pub participants: Vec<Participant>,
+    /// Only included when fetching a single Pullrequest
+    #[serde(default)]
+    pub reviewers: Vec<api::User>,
 }
 
 impl api::HtmlLink for PullRequest {
@@ -56,25 +103,275 @@ impl api::HtmlLink for PullRequest {
 }
 
 /// BitBucket data structure representing all possible states for a PullRequest
-#[derive(Debug, Copy, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PullRequestState {
     #[allow(missing_docs)]
-    MERGED,
+    Open,
     #[allow(missing_docs)]
-    SUPERSEDED,
+    Merged,
     #[allow(missing_docs)]
-    OPEN,
+    Declined,
     #[allow(missing_docs)]
-    DECLINED,
+    Superseded,
+    /// Any state unknown to this library, as returned by BitBucket
+    Other(String),
+}
+
+impl PullRequestState {
+    /// The state as used by BitBucket, e.g. "OPEN"
+    pub fn as_str(&self) -> &str {
+        match *self {
+            PullRequestState::Open => "OPEN",
+            PullRequestState::Merged => "MERGED",
+            PullRequestState::Declined => "DECLINED",
+            PullRequestState::Superseded => "SUPERSEDED",
+            PullRequestState::Other(ref state) => state,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for PullRequestState {
+    fn from(state: &'a str) -> PullRequestState {
+        match state {
+            "OPEN" => PullRequestState::Open,
+            "MERGED" => PullRequestState::Merged,
+            "DECLINED" => PullRequestState::Declined,
+            "SUPERSEDED" => PullRequestState::Superseded,
+            other => PullRequestState::Other(other.to_owned()),
+        }
+    }
 }
 
 impl fmt::Display for PullRequestState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            PullRequestState::MERGED => write!(f, "Merged"),
-            PullRequestState::SUPERSEDED => write!(f, "Superseeded"),
-            PullRequestState::OPEN => write!(f, "Open"),
-            PullRequestState::DECLINED => write!(f, "Declined"),
+            PullRequestState::Open => write!(f, "Open"),
+            PullRequestState::Merged => write!(f, "Merged"),
+            PullRequestState::Declined => write!(f, "Declined"),
+            PullRequestState::Superseded => write!(f, "Superseded"),
+            PullRequestState::Other(ref state) => write!(f, "{}", state),
         }
     }
-}
\ No newline at end of file
+}
+
+impl Serialize for PullRequestState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PullRequestState {
+    fn deserialize<D>(deserializer: D) -> Result<PullRequestState, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(|state| PullRequestState::from(state.as_str()))
+    }
+}
+
+/// BitBucket data structure representing a User taking part in a Pullrequest
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Participant {
+    pub user: api::User,
+    pub role: ParticipantRole,
+    pub approved: bool,
+    pub state: Option<ParticipantState>,
+    pub participated_on: Option<api::Timestamp>,
+}
+
+impl fmt::Display for Participant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.user)
+    }
+}
+
+/// BitBucket data structure representing the role of a Participant
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ParticipantRole {
+    #[allow(missing_docs)]
+    Participant,
+    #[allow(missing_docs)]
+    Reviewer,
+    /// Any role unknown to this library
+    #[serde(other)]
+    Unknown,
+}
+
+/// BitBucket data structure representing the review verdict of a Participant
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantState {
+    #[allow(missing_docs)]
+    Approved,
+    #[allow(missing_docs)]
+    ChangesRequested,
+    /// Any state unknown to this library
+    #[serde(other)]
+    Unknown,
+}
+
+/// BitBucket data structure representing the ways to merge a Pullrequest
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Always create a merge commit
+    MergeCommit,
+    /// Combine all commits into a single commit on the destination branch
+    Squash,
+    /// Only move the destination branch, fails if the branches diverged
+    FastForward,
+    /// Squash, failing if the branches diverged
+    SquashFastForward,
+    /// Rebase the commits onto the destination branch, then create a merge commit
+    RebaseMerge,
+    /// Rebase the commits onto the destination branch without merge commit
+    RebaseFastForward,
+    /// Any strategy unknown to this library, can't be used to merge as serializing it fails
+    #[serde(other)]
+    Unknown,
+}
+
+impl fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MergeStrategy::MergeCommit => write!(f, "Merge commit"),
+            MergeStrategy::Squash => write!(f, "Squash"),
+            MergeStrategy::FastForward => write!(f, "Fast forward"),
+            MergeStrategy::SquashFastForward => write!(f, "Squash, fast forward only"),
+            MergeStrategy::RebaseMerge => write!(f, "Rebase and merge"),
+            MergeStrategy::RebaseFastForward => write!(f, "Rebase, fast forward"),
+            MergeStrategy::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+impl Serialize for MergeStrategy {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let strategy = match *self {
+            MergeStrategy::MergeCommit => "merge_commit",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::FastForward => "fast_forward",
+            MergeStrategy::SquashFastForward => "squash_fast_forward",
+            MergeStrategy::RebaseMerge => "rebase_merge",
+            MergeStrategy::RebaseFastForward => "rebase_fast_forward",
+            MergeStrategy::Unknown => {
+                return Err(ser::Error::custom("unknown merge strategy can't be sent"))
+            }
+        };
+        serializer.serialize_str(strategy)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json;
+
+    /// Pull request as returned by BitBucket, shared with the tests of other pull request endpoints
+    pub(crate) const PULL_REQUEST_JSON: &str = r#"{
+        "id": 42,
+        "title": "Release 1.0",
+        "state": "OPEN",
+        "summary": {"type": "rendered", "raw": "Release", "markup": "markdown", "html": "<p>Release</p>"},
+        "description": "Release",
+        "author": {"username": "tester", "nickname": "tester", "display_name": "Tester", "uuid": "{1}", "links": {}},
+        "close_source_branch": true,
+        "closed_by": null,
+        "comment_count": 0,
+        "created_on": "2018-10-02T13:27:15.456178+00:00",
+        "destination": {
+            "branch": {"name": "master"},
+            "commit": {"hash": "abcdef012345", "links": {}},
+            "repository": {"full_name": "team/repo", "name": "repo", "uuid": "{2}", "links": {}}
+        },
+        "source": {
+            "branch": {"name": "release/1.0"},
+            "commit": {"hash": "012345abcdef", "links": {}},
+            "repository": {"full_name": "team/repo", "name": "repo", "uuid": "{2}", "links": {}}
+        },
+        "links": {"html": {"href": "https://bitbucket.org/team/repo/pull-requests/42"}},
+        "merge_commit": null,
+        "reason": "",
+        "task_count": 0,
+        "updated_on": "2018-10-02T13:27:15.456178+00:00",
+        "participants": [{
+            "user": {"username": "reviewer", "nickname": "reviewer", "display_name": "Reviewer", "uuid": "{3}", "links": {}},
+            "role": "REVIEWER",
+            "approved": false,
+            "state": null,
+            "participated_on": null
+        }],
+        "reviewers": [{"username": "reviewer", "nickname": "reviewer", "display_name": "Reviewer", "uuid": "{3}", "links": {}}]
+    }"#;
+
+    /// Participant as returned by BitBucket after approving a Pullrequest
+    pub(crate) const PARTICIPANT_JSON: &str = r#"{
+        "user": {"username": "tester", "nickname": "tester", "display_name": "Tester", "uuid": "{1}", "links": {}},
+        "role": "REVIEWER",
+        "approved": true,
+        "state": "approved",
+        "participated_on": "2018-10-02T13:27:15.456178+00:00"
+    }"#;
+
+    #[test]
+    fn deserializes_pull_request() {
+        let pr: PullRequest = serde_json::from_str(PULL_REQUEST_JSON).unwrap();
+        assert_eq!(pr.id, 42);
+        assert_eq!(
+            api::HtmlLink::get_url(&pr),
+            Some("https://bitbucket.org/team/repo/pull-requests/42")
+        );
+    }
+
+    #[test]
+    fn deserializes_typed_fields() {
+        let pr: PullRequest = serde_json::from_str(PULL_REQUEST_JSON).unwrap();
+        assert_eq!(pr.summary.raw, "Release");
+        assert!(pr.closed_by.is_none());
+        assert!(pr.merge_commit.is_none());
+        assert_eq!(pr.source.repository.full_name, "team/repo");
+        assert_eq!(pr.participants[0].role, ParticipantRole::Reviewer);
+        assert_eq!(pr.reviewers[0].username, "reviewer");
+    }
+
+    #[test]
+    fn unknown_states_are_kept() {
+        let states: Vec<PullRequestState> =
+            serde_json::from_str(r#"["MERGED", "DRAFT"]"#).unwrap();
+        assert_eq!(
+            states,
+            vec![
+                PullRequestState::Merged,
+                PullRequestState::Other("DRAFT".to_owned())
+            ]
+        );
+        assert_eq!(serde_json::to_string(&states[1]).unwrap(), r#""DRAFT""#);
+    }
+
+    #[test]
+    fn filters_by_state() {
+        let mut query = PullrequestsBuilder::new("tester");
+        query
+            .state(PullRequestState::Merged)
+            .state(PullRequestState::Declined);
+        assert_eq!(
+            api::GetQueryBuilder::get_query(&query).get_url(""),
+            "pullrequests/tester?pagelen=50&state=MERGED&state=DECLINED"
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parses_timestamps() {
+        let pr: PullRequest = serde_json::from_str(PULL_REQUEST_JSON).unwrap();
+        assert_eq!(pr.created_on.to_rfc3339(), "2018-10-02T13:27:15.456178+00:00");
+    }
+}
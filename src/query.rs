@@ -0,0 +1,367 @@
+//! The query module provides a typed builder for BitBucket's query language (BBQL),
+//! filtering and sorting the results of list endpoints.
+//! ```
+//! use bitbucket_api::api::Api;
+//! use bitbucket_api::query::{field, Direction};
+//!
+//! let api = Api::new("user", "app_password");
+//! let mut repositories = api.repositories("team");
+//! repositories
+//!     .query(field("name").contains("api").and(field("is_private").eq(true)))
+//!     .sort("updated_on", Direction::Descending);
+//! ```
+
+use std::fmt;
+
+use api::BitBucketQuery;
+
+/// Get a Field to build a condition on, e.g. `field("name").eq("bitbucket-api")`.
+/// Nested fields are separated by dots, e.g. `author.uuid`.
+pub fn field(name: &str) -> Field {
+    Field {
+        name: name.to_owned(),
+    }
+}
+
+/// Get a date or datetime Value, e.g. `date("2018-10-02")` or `date("2018-10-02T13:27:15+00:00")`
+pub fn date(value: &str) -> Value {
+    Value::Date(value.to_owned())
+}
+
+/// A field of the queried objects, see `field`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    name: String,
+}
+
+impl Field {
+    /// `field = value`
+    pub fn eq<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare("=", value.into())
+    }
+
+    /// `field != value`
+    pub fn ne<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare("!=", value.into())
+    }
+
+    /// `field ~ value`, the field contains the value, ignoring case
+    pub fn contains<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare("~", value.into())
+    }
+
+    /// `field !~ value`, the field does not contain the value, ignoring case
+    pub fn not_contains<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare("!~", value.into())
+    }
+
+    /// `field > value`
+    pub fn gt<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare(">", value.into())
+    }
+
+    /// `field >= value`
+    pub fn ge<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare(">=", value.into())
+    }
+
+    /// `field < value`
+    pub fn lt<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare("<", value.into())
+    }
+
+    /// `field <= value`
+    pub fn le<V: Into<Value>>(&self, value: V) -> Query {
+        self.compare("<=", value.into())
+    }
+
+    /// The field equals any of the values, rendered as `(field = a OR field = b)`
+    /// as BBQL has no list operator.
+    /// Without values no object matches, rendered as `(field = null AND field != null)`.
+    pub fn is_in<I, V>(&self, values: I) -> Query
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        let mut conditions = values.into_iter().map(|value| self.eq(value));
+        match conditions.next() {
+            Some(first) => conditions.fold(first, Query::or),
+            None => self.is_null().and(self.is_not_null()),
+        }
+    }
+
+    /// `field = null`
+    pub fn is_null(&self) -> Query {
+        self.compare("=", Value::Null)
+    }
+
+    /// `field != null`
+    pub fn is_not_null(&self) -> Query {
+        self.compare("!=", Value::Null)
+    }
+
+    fn compare(&self, operator: &'static str, value: Value) -> Query {
+        Query {
+            expression: Expression::Condition {
+                field: self.name.to_owned(),
+                operator,
+                value,
+            },
+        }
+    }
+}
+
+/// A value compared to a Field
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Rendered quoted, e.g. `"OPEN"`
+    String(String),
+    #[allow(missing_docs)]
+    Number(i64),
+    #[allow(missing_docs)]
+    Bool(bool),
+    /// Rendered as is, e.g. `2018-10-02T13:27:15+00:00`, see `date`
+    Date(String),
+    #[allow(missing_docs)]
+    Null,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::String(ref value) => {
+                let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "\"{}\"", escaped)
+            }
+            Value::Number(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Date(ref value) => write!(f, "{}", value),
+            Value::Null => write!(f, "null"),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(value: &'a str) -> Value {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::String(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Value {
+        Value::Number(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Value {
+        Value::Number(i64::from(value))
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Value {
+        Value::Number(i64::from(value))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Value {
+        Value::Bool(value)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<::chrono::DateTime<::chrono::Utc>> for Value {
+    fn from(value: ::chrono::DateTime<::chrono::Utc>) -> Value {
+        Value::Date(value.to_rfc3339())
+    }
+}
+
+/// A BBQL expression, built from `field` conditions combined with `and` and `or`.
+/// The Display implementation renders the expression as BBQL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    expression: Expression,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    Condition {
+        field: String,
+        operator: &'static str,
+        value: Value,
+    },
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+}
+
+impl Query {
+    /// Combines both expressions with `AND`
+    pub fn and(self, other: Query) -> Query {
+        Query {
+            expression: Expression::And(Box::new(self), Box::new(other)),
+        }
+    }
+
+    /// Combines both expressions with `OR`
+    pub fn or(self, other: Query) -> Query {
+        Query {
+            expression: Expression::Or(Box::new(self), Box::new(other)),
+        }
+    }
+
+    /// Renders an operand, parenthesized unless it is a condition or uses the same operator
+    fn fmt_operand(&self, operand: &Query, f: &mut fmt::Formatter) -> fmt::Result {
+        let parenthesize = !matches!(
+            (&self.expression, &operand.expression),
+            (_, &Expression::Condition { .. })
+                | (&Expression::And(..), &Expression::And(..))
+                | (&Expression::Or(..), &Expression::Or(..))
+        );
+        if parenthesize {
+            write!(f, "({})", operand)
+        } else {
+            write!(f, "{}", operand)
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (left, operator, right) = match self.expression {
+            Expression::Condition {
+                ref field,
+                operator,
+                ref value,
+            } => return write!(f, "{} {} {}", field, operator, value),
+            Expression::And(ref left, ref right) => (left, "AND", right),
+            Expression::Or(ref left, ref right) => (left, "OR", right),
+        };
+        self.fmt_operand(left, f)?;
+        write!(f, " {} ", operator)?;
+        self.fmt_operand(right, f)
+    }
+}
+
+/// Order of sorted results
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Direction {
+    Ascending,
+    Descending,
+}
+
+/// Sort order of a list endpoint, rendered as `field` or `-field`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sort {
+    field: String,
+    direction: Direction,
+}
+
+impl Sort {
+    /// Get a Sort by the given field in the given direction
+    pub fn new(field: &str, direction: Direction) -> Sort {
+        Sort {
+            field: field.to_owned(),
+            direction,
+        }
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.direction {
+            Direction::Ascending => write!(f, "{}", self.field),
+            Direction::Descending => write!(f, "-{}", self.field),
+        }
+    }
+}
+
+/// Adds the `q` and `sort` parameters, URL encoded, to a query.
+pub(crate) fn apply(
+    query: BitBucketQuery,
+    q: Option<&Query>,
+    sort: Option<&Sort>,
+) -> BitBucketQuery {
+    let query = match q {
+        Some(q) => query.with_param("q", &q.to_string()),
+        None => query,
+    };
+    match sort {
+        Some(sort) => query.with_param("sort", &sort.to_string()),
+        None => query,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::{Api, GetQueryBuilder};
+
+    #[test]
+    fn renders_conditions() {
+        assert_eq!(field("name").eq("api").to_string(), r#"name = "api""#);
+        assert_eq!(
+            field("title").contains(r#"say "hi"\"#).to_string(),
+            r#"title ~ "say \"hi\"\\""#
+        );
+        assert_eq!(
+            field("updated_on")
+                .gt(date("2018-10-02T13:27:15+00:00"))
+                .to_string(),
+            "updated_on > 2018-10-02T13:27:15+00:00"
+        );
+        assert_eq!(field("closed_by").is_null().to_string(), "closed_by = null");
+        assert_eq!(field("size").le(1024).to_string(), "size <= 1024");
+    }
+
+    #[test]
+    fn parenthesizes_mixed_operators() {
+        let query = field("state")
+            .is_in(vec!["OPEN", "MERGED"])
+            .and(field("author.uuid").ne("{1}"))
+            .and(field("is_private").eq(false));
+        assert_eq!(
+            query.to_string(),
+            r#"(state = "OPEN" OR state = "MERGED") AND author.uuid != "{1}" AND is_private = false"#
+        );
+    }
+
+    #[test]
+    fn is_in_without_values_matches_nothing() {
+        let query = field("state")
+            .is_in(Vec::<&str>::new())
+            .or(field("author.uuid").eq("{1}"));
+        assert_eq!(
+            query.to_string(),
+            r#"(state = null AND state != null) OR author.uuid = "{1}""#
+        );
+    }
+
+    #[test]
+    fn builders_encode_query_and_sort() {
+        let api = Api::new("tester", "secret");
+        let mut pullrequests = api.repositories("team").repo_slug("repo").pullrequests();
+        pullrequests
+            .query(field("title").contains("fix & test"))
+            .sort("updated_on", Direction::Descending);
+        assert_eq!(
+            pullrequests.get_query().get_url(""),
+            "repositories/team/repo/pullrequests?pagelen=50&q=title+%7E+%22fix+%26+test%22&sort=-updated_on"
+        );
+
+        let mut repositories = api.repositories("team");
+        repositories.filter("api");
+        assert_eq!(
+            repositories.get_query().get_url(""),
+            "repositories/team?pagelen=75&q=name+%7E+%22api%22"
+        );
+    }
+}
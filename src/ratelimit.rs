@@ -0,0 +1,174 @@
+//! The ratelimit module keeps the Api within BitBucket's request quota.
+//!
+//! `RateLimiter` throttles requests on the client side, while `RateLimitStatus`
+//! reports the rate limit headers returned by BitBucket.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use transport::Response;
+
+/// Token bucket limiting the number of requests sent, see `ApiBuilder::rate_limiter`.
+///
+/// Clones share the same bucket, so all clones of an Api, or several Apis configured
+/// with clones of the same limiter, stay within the limit together.
+/// Requests exceeding the limit block until they are allowed.
+/// ```
+/// use bitbucket_api::api::ApiBuilder;
+/// use bitbucket_api::ratelimit::RateLimiter;
+///
+/// let api = ApiBuilder::new("user", "app_password")
+///     .rate_limiter(RateLimiter::per_hour(1000))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    tokens: f64,
+    tokens_per_second: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Get a limiter allowing `requests` requests per `period`,
+    /// which may all be sent at once after being idle for a full period.
+    pub fn new(requests: u32, period: Duration) -> RateLimiter {
+        let capacity = f64::from(requests.max(1));
+        RateLimiter {
+            bucket: Arc::new(Mutex::new(Bucket {
+                capacity,
+                tokens: capacity,
+                tokens_per_second: capacity / period.as_secs_f64().max(f64::EPSILON),
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Get a limiter allowing `requests` requests per second
+    pub fn per_second(requests: u32) -> RateLimiter {
+        RateLimiter::new(requests, Duration::from_secs(1))
+    }
+
+    /// Get a limiter allowing `requests` requests per hour
+    pub fn per_hour(requests: u32) -> RateLimiter {
+        RateLimiter::new(requests, Duration::from_secs(60 * 60))
+    }
+
+    /// Blocks until the next request may be sent.
+    pub(crate) fn acquire(&self) {
+        let wait = self.reserve();
+        if wait > Duration::from_secs(0) {
+            debug!("Rate limit reached, waiting {:?}", wait);
+            thread::sleep(wait);
+        }
+    }
+
+    /// Takes a token from the bucket, returning how long to wait until it is available.
+    /// Tokens are reserved in advance, so waiting happens without holding the lock.
+    fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().expect("RateLimiter lock poisoned");
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * bucket.tokens_per_second).min(bucket.capacity);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / bucket.tokens_per_second)
+        }
+    }
+}
+
+/// Rate limit information reported by BitBucket in the headers of the latest response,
+/// see `Api::rate_limit_status`. BitBucket only sends the headers on some endpoints,
+/// so each value is optional.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitStatus {
+    /// Number of requests allowed in the current window, `X-RateLimit-Limit`
+    pub limit: Option<u64>,
+    /// Number of requests left in the current window, `X-RateLimit-Remaining`
+    pub remaining: Option<u64>,
+    /// Seconds until the window resets, `X-RateLimit-Reset`
+    pub reset: Option<u64>,
+    /// The quota the request counted against, `X-RateLimit-Resource`
+    pub resource: Option<String>,
+    /// Whether less than 20% of the quota are left, `X-RateLimit-NearLimit`
+    pub near_limit: Option<bool>,
+}
+
+impl RateLimitStatus {
+    /// Reads the rate limit headers of a response, None if there are none.
+    pub(crate) fn from_response(response: &Response) -> Option<RateLimitStatus> {
+        let number = |name: &str| {
+            response
+                .header(name)
+                .and_then(|value| value.trim().parse().ok())
+        };
+        let status = RateLimitStatus {
+            limit: number("X-RateLimit-Limit"),
+            remaining: number("X-RateLimit-Remaining"),
+            reset: number("X-RateLimit-Reset"),
+            resource: response
+                .header("X-RateLimit-Resource")
+                .map(|value| value.trim().to_owned()),
+            near_limit: response
+                .header("X-RateLimit-NearLimit")
+                .map(|value| value.trim().eq_ignore_ascii_case("true")),
+        };
+        if status == RateLimitStatus::default() {
+            None
+        } else {
+            Some(status)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_bursts_up_to_capacity() {
+        let limiter = RateLimiter::per_hour(3);
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+
+        let wait = limiter.reserve();
+        assert!(wait > Duration::from_secs(1190) && wait <= Duration::from_secs(1200));
+    }
+
+    #[test]
+    fn clones_share_the_bucket() {
+        let limiter = RateLimiter::per_hour(1);
+        let clone = limiter.clone();
+        assert_eq!(limiter.reserve(), Duration::from_secs(0));
+        assert!(clone.reserve() > Duration::from_secs(0));
+    }
+
+    #[test]
+    fn reads_rate_limit_headers() {
+        let response = Response::new(200, b"")
+            .with_header("X-RateLimit-Limit", "1000")
+            .with_header("X-RateLimit-Resource", "api")
+            .with_header("X-RateLimit-NearLimit", "false");
+        let status = RateLimitStatus::from_response(&response).unwrap();
+
+        assert_eq!(status.limit, Some(1000));
+        assert_eq!(status.remaining, None);
+        assert_eq!(status.resource, Some("api".to_owned()));
+        assert_eq!(status.near_limit, Some(false));
+
+        assert_eq!(
+            RateLimitStatus::from_response(&Response::new(200, b"")),
+            None
+        );
+    }
+}
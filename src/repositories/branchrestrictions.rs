@@ -1,7 +1,10 @@
 use api;
+use users::User;
 
-use serde_json;
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Clone, Debug)]
 pub struct BranchRestrictionBuilder {
@@ -16,30 +19,630 @@ impl BranchRestrictionBuilder {
             repo_slug: repo_slug.to_owned(),
         }
     }
+
+    pub fn id(&self, id: u64) -> RestrictionBuilder {
+        RestrictionBuilder::new(&self.url_path(), id)
+    }
+
+    pub fn restriction(&self, restriction: &BranchPermission) -> RestrictionBuilder {
+        self.id(restriction.id)
+    }
+
+    /// Prepares adding a branch restriction, send it using Api::post.
+    pub fn create(&self, spec: &BranchRestrictionSpec) -> CreateBranchRestrictionBuilder {
+        CreateBranchRestrictionBuilder {
+            url_path: self.url_path(),
+            spec: spec.clone(),
+        }
+    }
+
+    fn url_path(&self) -> String {
+        format!(
+            "repositories/{}/{}/branch-restrictions",
+            self.username, self.repo_slug,
+        )
+    }
 }
 
 impl api::GetQueryBuilder for BranchRestrictionBuilder {
     type Item = BranchPermission;
     fn get_query(&self) -> api::BitBucketQuery {
-        let url_path = format!(
-            "repositories/{}/{}/branch-restrictions",
-            self.username, self.repo_slug,
-        );
-        api::BitBucketQuery::new(url_path)
+        api::BitBucketQuery::new(self.url_path())
     }
 }
 
-/// BitBucket data structure representing a single Repository
+/// Single branch restriction, fetched using Api::get and removed using Api::delete
+#[derive(Clone, Debug)]
+pub struct RestrictionBuilder {
+    url_path: String,
+}
+
+impl RestrictionBuilder {
+    pub(crate) fn new(restrictions_path: &str, id: u64) -> RestrictionBuilder {
+        RestrictionBuilder {
+            url_path: format!("{}/{}", restrictions_path, id),
+        }
+    }
+
+    /// Prepares replacing the branch restriction, send it using Api::put.
+    pub fn update(&self, spec: &BranchRestrictionSpec) -> UpdateBranchRestrictionBuilder {
+        UpdateBranchRestrictionBuilder {
+            url_path: self.url_path.to_owned(),
+            spec: spec.clone(),
+        }
+    }
+}
+
+impl api::GetQueryBuilder for RestrictionBuilder {
+    type Item = BranchPermission;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+}
+
+impl api::DeleteQueryBuilder for RestrictionBuilder {
+    fn delete_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateBranchRestrictionBuilder {
+    url_path: String,
+    spec: BranchRestrictionSpec,
+}
+
+impl api::PostQueryBuilder for CreateBranchRestrictionBuilder {
+    type Body = BranchRestrictionSpec;
+    type Item = BranchPermission;
+    fn post_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+    fn post_body(&self) -> Option<&BranchRestrictionSpec> {
+        Some(&self.spec)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UpdateBranchRestrictionBuilder {
+    url_path: String,
+    spec: BranchRestrictionSpec,
+}
+
+impl api::PutQueryBuilder for UpdateBranchRestrictionBuilder {
+    type Body = BranchRestrictionSpec;
+    type Item = BranchPermission;
+    fn put_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+    fn put_body(&self) -> &BranchRestrictionSpec {
+        &self.spec
+    }
+}
+
+/// BitBucket data structure representing a single branch restriction
 #[derive(Debug, Clone, Deserialize)]
 #[allow(missing_docs)]
 pub struct BranchPermission {
     pub id: u64,
-    pub kind: String,
-    pub links: HashMap<String, api::Link>,
+    pub kind: BranchRestrictionKind,
+    #[serde(default)]
+    pub branch_match_kind: BranchMatchKind,
+    /// The branches affected by the restriction if matched by branch_type
+    pub branch_type: Option<BranchType>,
+    /// The branches affected by the restriction if matched by glob
+    #[serde(default)]
     pub pattern: String,
+    pub links: HashMap<String, api::Link>,
     #[serde(rename = "type")]
     pub bb_type: String,
-    pub users: serde_json::Value,
-    pub groups: serde_json::Value,
-    pub value: Option<serde_json::Value>,
+    /// Users exempt from the restriction, e.g. allowed to push
+    #[serde(default)]
+    pub users: Vec<User>,
+    /// Groups exempt from the restriction
+    #[serde(default)]
+    pub groups: Vec<Group>,
+    /// Threshold of the restriction, e.g. the number of required approvals
+    pub value: Option<u32>,
+}
+
+impl BranchPermission {
+    /// The settings of the restriction, e.g. to update or copy it
+    pub fn spec(&self) -> BranchRestrictionSpec {
+        let pattern = match self.branch_match_kind {
+            BranchMatchKind::Glob => Some(self.pattern.to_owned()),
+            BranchMatchKind::BranchingModel => None,
+        };
+        BranchRestrictionSpec {
+            kind: self.kind.clone(),
+            branch_match_kind: self.branch_match_kind,
+            pattern,
+            branch_type: self.branch_type.clone(),
+            users: self
+                .users
+                .iter()
+                .map(|user| UserRef {
+                    uuid: user.uuid.to_owned(),
+                })
+                .collect(),
+            groups: self
+                .groups
+                .iter()
+                .map(|group| GroupRef {
+                    slug: group.slug.to_owned(),
+                })
+                .collect(),
+            value: self.value,
+        }
+    }
+}
+
+impl api::HtmlLink for BranchPermission {
+    fn links(&self) -> &HashMap<String, api::Link> {
+        &self.links
+    }
+}
+
+/// Settings of a branch restriction, sent when creating or updating it.
+/// When deserialized, e.g. as part of a `Policy`, a missing pattern matches all branches
+/// like `BranchRestrictionSpec::new`, and pattern or branch_type must fit branch_match_kind.
+/// ```
+/// use bitbucket_api::api::{BranchRestrictionKind, BranchRestrictionSpec};
+///
+/// let spec = BranchRestrictionSpec::new(BranchRestrictionKind::RequireApprovalsToMerge)
+///     .pattern("master")
+///     .value(2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BranchRestrictionSpec {
+    #[allow(missing_docs)]
+    pub kind: BranchRestrictionKind,
+    /// Whether the branches are matched by pattern or branch_type
+    pub branch_match_kind: BranchMatchKind,
+    /// Glob pattern of the affected branches, e.g. `release/*`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Type of the affected branches according to the branching model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_type: Option<BranchType>,
+    /// Users exempt from the restriction
+    pub users: Vec<UserRef>,
+    /// Groups exempt from the restriction
+    pub groups: Vec<GroupRef>,
+    /// Threshold of the restriction, e.g. the number of required approvals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<u32>,
+}
+
+/// BranchRestrictionSpec as declared, before defaults and validation
+#[derive(Deserialize)]
+struct DeclaredSpec {
+    kind: BranchRestrictionKind,
+    #[serde(default)]
+    branch_match_kind: BranchMatchKind,
+    #[serde(default)]
+    pattern: Option<String>,
+    #[serde(default)]
+    branch_type: Option<BranchType>,
+    #[serde(default)]
+    users: Vec<UserRef>,
+    #[serde(default)]
+    groups: Vec<GroupRef>,
+    #[serde(default)]
+    value: Option<u32>,
+}
+
+impl<'de> Deserialize<'de> for BranchRestrictionSpec {
+    fn deserialize<D>(deserializer: D) -> Result<BranchRestrictionSpec, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let declared = DeclaredSpec::deserialize(deserializer)?;
+        let pattern = match (
+            declared.branch_match_kind,
+            declared.pattern,
+            &declared.branch_type,
+        ) {
+            (BranchMatchKind::Glob, pattern, None) => {
+                Some(pattern.unwrap_or_else(|| "*".to_owned()))
+            }
+            (BranchMatchKind::BranchingModel, None, Some(_)) => None,
+            (BranchMatchKind::Glob, _, Some(_)) => {
+                return Err(de::Error::custom(
+                    "branch_type requires branch_match_kind branching_model",
+                ))
+            }
+            (BranchMatchKind::BranchingModel, Some(_), _) => {
+                return Err(de::Error::custom("pattern requires branch_match_kind glob"))
+            }
+            (BranchMatchKind::BranchingModel, None, None) => {
+                return Err(de::Error::custom(
+                    "branch_match_kind branching_model requires a branch_type",
+                ))
+            }
+        };
+        Ok(BranchRestrictionSpec {
+            kind: declared.kind,
+            branch_match_kind: declared.branch_match_kind,
+            pattern,
+            branch_type: declared.branch_type,
+            users: declared.users,
+            groups: declared.groups,
+            value: declared.value,
+        })
+    }
+}
+
+impl BranchRestrictionSpec {
+    /// Get a restriction of the given kind, applying to all branches
+    pub fn new(kind: BranchRestrictionKind) -> BranchRestrictionSpec {
+        BranchRestrictionSpec {
+            kind,
+            branch_match_kind: BranchMatchKind::Glob,
+            pattern: Some("*".to_owned()),
+            branch_type: None,
+            users: Vec::new(),
+            groups: Vec::new(),
+            value: None,
+        }
+    }
+
+    /// Applies the restriction to branches matching the glob pattern
+    pub fn pattern(mut self, pattern: &str) -> BranchRestrictionSpec {
+        self.branch_match_kind = BranchMatchKind::Glob;
+        self.pattern = Some(pattern.to_owned());
+        self.branch_type = None;
+        self
+    }
+
+    /// Applies the restriction to branches of the given type in the branching model
+    pub fn branch_type(mut self, branch_type: BranchType) -> BranchRestrictionSpec {
+        self.branch_match_kind = BranchMatchKind::BranchingModel;
+        self.branch_type = Some(branch_type);
+        self.pattern = None;
+        self
+    }
+
+    /// Exempts the user from the restriction
+    pub fn user(self, user: &User) -> BranchRestrictionSpec {
+        self.user_uuid(&user.uuid)
+    }
+
+    /// Exempts the user with the given uuid from the restriction
+    pub fn user_uuid(mut self, uuid: &str) -> BranchRestrictionSpec {
+        self.users.push(UserRef {
+            uuid: uuid.to_owned(),
+        });
+        self
+    }
+
+    /// Exempts the group with the given slug from the restriction
+    pub fn group_slug(mut self, slug: &str) -> BranchRestrictionSpec {
+        self.groups.push(GroupRef {
+            slug: slug.to_owned(),
+        });
+        self
+    }
+
+    /// Sets the threshold of the restriction, e.g. the number of required approvals
+    pub fn value(mut self, value: u32) -> BranchRestrictionSpec {
+        self.value = Some(value);
+        self
+    }
+}
+
+/// Reference to a User by uuid
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct UserRef {
+    pub uuid: String,
+}
+
+/// Reference to a Group by slug
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct GroupRef {
+    pub slug: String,
+}
+
+/// BitBucket data structure representing a group of users
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Group {
+    pub name: String,
+    pub slug: String,
+    pub full_slug: Option<String>,
+    pub owner: Option<User>,
+    #[serde(default)]
+    pub links: HashMap<String, api::Link>,
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// BitBucket data structure representing the ways branches are matched by a restriction
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchMatchKind {
+    /// Matched by pattern
+    #[default]
+    Glob,
+    /// Matched by branch_type
+    BranchingModel,
+}
+
+/// BitBucket data structure representing the branch types of the branching model
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum BranchType {
+    Feature,
+    Bugfix,
+    Release,
+    Hotfix,
+    Development,
+    Production,
+    /// Any branch type unknown to this library, as used by BitBucket
+    Other(String),
+}
+
+impl BranchType {
+    /// The branch type as used by BitBucket, e.g. "release"
+    pub fn as_str(&self) -> &str {
+        match *self {
+            BranchType::Feature => "feature",
+            BranchType::Bugfix => "bugfix",
+            BranchType::Release => "release",
+            BranchType::Hotfix => "hotfix",
+            BranchType::Development => "development",
+            BranchType::Production => "production",
+            BranchType::Other(ref branch_type) => branch_type,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for BranchType {
+    fn from(branch_type: &'a str) -> BranchType {
+        match branch_type {
+            "feature" => BranchType::Feature,
+            "bugfix" => BranchType::Bugfix,
+            "release" => BranchType::Release,
+            "hotfix" => BranchType::Hotfix,
+            "development" => BranchType::Development,
+            "production" => BranchType::Production,
+            other => BranchType::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for BranchType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for BranchType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for BranchType {
+    fn deserialize<D>(deserializer: D) -> Result<BranchType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(|branch_type| BranchType::from(branch_type.as_str()))
+    }
+}
+
+/// BitBucket data structure representing the kinds of branch restrictions
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum BranchRestrictionKind {
+    Push,
+    Force,
+    Delete,
+    RestrictMerges,
+    RequireTasksToBeCompleted,
+    RequireApprovalsToMerge,
+    RequireDefaultReviewerApprovalsToMerge,
+    RequireNoChangesRequested,
+    RequirePassingBuildsToMerge,
+    RequireCommitsBehind,
+    ResetPullrequestApprovalsOnChange,
+    EnforceMergeChecks,
+    /// Any kind unknown to this library, as used by BitBucket
+    Other(String),
+}
+
+impl BranchRestrictionKind {
+    /// The kind as used by BitBucket, e.g. "require_approvals_to_merge"
+    pub fn as_str(&self) -> &str {
+        match *self {
+            BranchRestrictionKind::Push => "push",
+            BranchRestrictionKind::Force => "force",
+            BranchRestrictionKind::Delete => "delete",
+            BranchRestrictionKind::RestrictMerges => "restrict_merges",
+            BranchRestrictionKind::RequireTasksToBeCompleted => "require_tasks_to_be_completed",
+            BranchRestrictionKind::RequireApprovalsToMerge => "require_approvals_to_merge",
+            BranchRestrictionKind::RequireDefaultReviewerApprovalsToMerge => {
+                "require_default_reviewer_approvals_to_merge"
+            }
+            BranchRestrictionKind::RequireNoChangesRequested => "require_no_changes_requested",
+            BranchRestrictionKind::RequirePassingBuildsToMerge => "require_passing_builds_to_merge",
+            BranchRestrictionKind::RequireCommitsBehind => "require_commits_behind",
+            BranchRestrictionKind::ResetPullrequestApprovalsOnChange => {
+                "reset_pullrequest_approvals_on_change"
+            }
+            BranchRestrictionKind::EnforceMergeChecks => "enforce_merge_checks",
+            BranchRestrictionKind::Other(ref kind) => kind,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for BranchRestrictionKind {
+    fn from(kind: &'a str) -> BranchRestrictionKind {
+        match kind {
+            "push" => BranchRestrictionKind::Push,
+            "force" => BranchRestrictionKind::Force,
+            "delete" => BranchRestrictionKind::Delete,
+            "restrict_merges" => BranchRestrictionKind::RestrictMerges,
+            "require_tasks_to_be_completed" => BranchRestrictionKind::RequireTasksToBeCompleted,
+            "require_approvals_to_merge" => BranchRestrictionKind::RequireApprovalsToMerge,
+            "require_default_reviewer_approvals_to_merge" => {
+                BranchRestrictionKind::RequireDefaultReviewerApprovalsToMerge
+            }
+            "require_no_changes_requested" => BranchRestrictionKind::RequireNoChangesRequested,
+            "require_passing_builds_to_merge" => BranchRestrictionKind::RequirePassingBuildsToMerge,
+            "require_commits_behind" => BranchRestrictionKind::RequireCommitsBehind,
+            "reset_pullrequest_approvals_on_change" => {
+                BranchRestrictionKind::ResetPullrequestApprovalsOnChange
+            }
+            "enforce_merge_checks" => BranchRestrictionKind::EnforceMergeChecks,
+            other => BranchRestrictionKind::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for BranchRestrictionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for BranchRestrictionKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for BranchRestrictionKind {
+    fn deserialize<D>(deserializer: D) -> Result<BranchRestrictionKind, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(|kind| BranchRestrictionKind::from(kind.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::Api;
+    use serde_json;
+    use serde_json::json;
+    use transport::{MemoryTransport, Method, Response};
+
+    const RESTRICTIONS_URL: &str =
+        "https://api.bitbucket.org/2.0/repositories/team/repo/branch-restrictions";
+
+    const RESTRICTION_JSON: &str = r#"{
+        "type": "branchrestriction",
+        "id": 7,
+        "kind": "push",
+        "branch_match_kind": "branching_model",
+        "branch_type": "release",
+        "pattern": "",
+        "users": [{"username": "tester", "nickname": "tester", "display_name": "Tester", "uuid": "{1}", "links": {}}],
+        "groups": [{"name": "Developers", "slug": "developers", "links": {}}],
+        "value": null,
+        "links": {}
+    }"#;
+
+    #[test]
+    fn deserializes_typed_restriction() {
+        let restriction: BranchPermission = serde_json::from_str(RESTRICTION_JSON).unwrap();
+        assert_eq!(restriction.kind, BranchRestrictionKind::Push);
+        assert_eq!(restriction.branch_type, Some(BranchType::Release));
+        assert_eq!(restriction.groups[0].slug, "developers");
+        assert_eq!(
+            restriction.spec(),
+            BranchRestrictionSpec::new(BranchRestrictionKind::Push)
+                .branch_type(BranchType::Release)
+                .user_uuid("{1}")
+                .group_slug("developers")
+        );
+
+        let unknown: BranchRestrictionKind = serde_json::from_str(r#""allow_auto_merge""#).unwrap();
+        assert_eq!(unknown.as_str(), "allow_auto_merge");
+        let unknown: BranchType = serde_json::from_str(r#""support""#).unwrap();
+        assert_eq!(unknown, BranchType::Other("support".to_owned()));
+        assert_eq!(serde_json::to_string(&unknown).unwrap(), r#""support""#);
+    }
+
+    #[test]
+    fn deserializes_spec_like_new() {
+        let spec: BranchRestrictionSpec = serde_json::from_str(r#"{"kind": "force"}"#).unwrap();
+        assert_eq!(
+            spec,
+            BranchRestrictionSpec::new(BranchRestrictionKind::Force)
+        );
+
+        let mismatched = [
+            r#"{"kind": "push", "branch_type": "release"}"#,
+            r#"{"kind": "push", "branch_match_kind": "branching_model"}"#,
+            r#"{"kind": "push", "branch_match_kind": "branching_model", "branch_type": "release", "pattern": "*"}"#,
+        ];
+        for json in &mismatched {
+            assert!(serde_json::from_str::<BranchRestrictionSpec>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn creates_updates_and_deletes() {
+        let url = format!("{}/7", RESTRICTIONS_URL);
+        let transport = MemoryTransport::new();
+        transport
+            .respond(
+                Method::Post,
+                RESTRICTIONS_URL,
+                Response::new(201, RESTRICTION_JSON.as_bytes()),
+            )
+            .respond(
+                Method::Put,
+                &url,
+                Response::new(200, RESTRICTION_JSON.as_bytes()),
+            )
+            .respond(Method::Delete, &url, Response::new(204, b""));
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        let restrictions = api
+            .repositories("team")
+            .repo_slug("repo")
+            .branch_restrictions();
+        let spec = BranchRestrictionSpec::new(BranchRestrictionKind::RequireApprovalsToMerge)
+            .pattern("master")
+            .value(2);
+
+        let created = api.post(&restrictions.create(&spec)).unwrap();
+        let restriction = restrictions.restriction(&created);
+        api.put(&restriction.update(&spec.clone().value(3)))
+            .unwrap();
+        api.delete(&restriction).unwrap();
+
+        let requests = transport.requests();
+        let body: serde_json::Value =
+            serde_json::from_slice(requests[0].body.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "kind": "require_approvals_to_merge",
+                "branch_match_kind": "glob",
+                "pattern": "master",
+                "users": [],
+                "groups": [],
+                "value": 2
+            })
+        );
+        assert_eq!(requests[1].url, url);
+        assert_eq!(requests[2].method, Method::Delete);
+    }
 }
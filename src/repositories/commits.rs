@@ -0,0 +1,187 @@
+use api;
+use query::{self, Direction, Query, Sort};
+use repositories::refs;
+
+#[derive(Clone, Debug)]
+pub struct CommitsBuilder {
+    repository_path: String,
+    revision: Option<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    path: Option<String>,
+    query: Option<Query>,
+    sort: Option<Sort>,
+}
+
+impl CommitsBuilder {
+    pub(crate) fn new(repository_path: &str) -> CommitsBuilder {
+        CommitsBuilder {
+            repository_path: repository_path.to_owned(),
+            revision: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            path: None,
+            query: None,
+            sort: None,
+        }
+    }
+
+    /// Only returns commits reachable from the given branch, tag or commit hash
+    pub fn branch(&mut self, revision: &str) -> &mut CommitsBuilder {
+        self.revision = Some(revision.to_owned());
+        self
+    }
+
+    /// Also returns commits reachable from the given revision, may be called multiple times
+    pub fn include(&mut self, revision: &str) -> &mut CommitsBuilder {
+        self.include.push(revision.to_owned());
+        self
+    }
+
+    /// Omits commits reachable from the given revision, may be called multiple times.
+    /// E.g. excluding the previous release tag returns the commits since that release.
+    pub fn exclude(&mut self, revision: &str) -> &mut CommitsBuilder {
+        self.exclude.push(revision.to_owned());
+        self
+    }
+
+    /// Only returns commits changing the given file or directory
+    pub fn path(&mut self, path: &str) -> &mut CommitsBuilder {
+        self.path = Some(path.to_owned());
+        self
+    }
+
+    /// Only returns commits matching the query, see the query module
+    pub fn query(&mut self, query: Query) -> &mut CommitsBuilder {
+        self.query = Some(query);
+        self
+    }
+
+    /// Sorts the results by the given field, see the query module
+    pub fn sort(&mut self, field: &str, direction: Direction) -> &mut CommitsBuilder {
+        self.sort = Some(Sort::new(field, direction));
+        self
+    }
+}
+
+impl api::GetQueryBuilder for CommitsBuilder {
+    type Item = api::Commit;
+    fn get_query(&self) -> api::BitBucketQuery {
+        let commits_path = format!("{}/commits", self.repository_path);
+        let url_path = match self.revision {
+            Some(ref revision) => refs::ref_path(&commits_path, revision),
+            None => commits_path,
+        };
+        let mut query = api::BitBucketQuery::new(url_path);
+        for revision in &self.include {
+            query = query.add_param("include", revision);
+        }
+        for revision in &self.exclude {
+            query = query.add_param("exclude", revision);
+        }
+        if let Some(ref path) = self.path {
+            query = query.with_param("path", path);
+        }
+        query::apply(query, self.query.as_ref(), self.sort.as_ref())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CommitBuilder {
+    url_path: String,
+}
+
+impl CommitBuilder {
+    pub(crate) fn new(repository_path: &str, hash: &str) -> CommitBuilder {
+        CommitBuilder {
+            url_path: refs::ref_path(&format!("{}/commit", repository_path), hash),
+        }
+    }
+}
+
+impl api::GetQueryBuilder for CommitBuilder {
+    type Item = api::Commit;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::{Api, GetQueryBuilder};
+    use query::{field, Direction};
+    use transport::{MemoryTransport, Method, Response};
+
+    #[test]
+    fn traverses_history() {
+        let api = Api::new("tester", "secret");
+        let mut commits = api.repositories("team").repo_slug("repo").commits();
+        commits
+            .branch("release/1.0")
+            .include("hotfix")
+            .exclude("v0.9")
+            .exclude("master")
+            .path("src/lib.rs");
+        assert_eq!(
+            commits.get_query().get_url(""),
+            "repositories/team/repo/commits/release%2F1.0?include=hotfix&exclude=v0.9&exclude=master&path=src%2Flib.rs"
+        );
+    }
+
+    #[test]
+    fn filters_and_sorts_commits() {
+        let api = Api::new("tester", "secret");
+        let mut commits = api.repositories("team").repo_slug("repo").commits();
+        commits
+            .query(field("author.raw").contains("tester"))
+            .sort("date", Direction::Ascending);
+        assert_eq!(
+            commits.get_query().get_url(""),
+            "repositories/team/repo/commits?q=author.raw+%7E+%22tester%22&sort=date"
+        );
+    }
+
+    #[test]
+    fn fetches_full_commit() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team/repo/commit/012345abcdef",
+            Response::new(
+                200,
+                br#"{
+                    "type": "commit",
+                    "hash": "012345abcdef",
+                    "message": "Add commits endpoint\n",
+                    "summary": {"type": "rendered", "raw": "Add commits endpoint\n", "markup": "markdown", "html": "<p>Add commits endpoint</p>"},
+                    "date": "2018-10-02T13:27:15+00:00",
+                    "author": {
+                        "type": "author",
+                        "raw": "Tester <tester@example.com>",
+                        "user": {"username": "tester", "nickname": "tester", "display_name": "Tester", "uuid": "{1}", "links": {}}
+                    },
+                    "parents": [{"type": "commit", "hash": "abcdef012345", "links": {}}],
+                    "links": {"html": {"href": "https://bitbucket.org/team/repo/commits/012345abcdef"}}
+                }"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        let commit = api
+            .get(
+                &api.repositories("team")
+                    .repo_slug("repo")
+                    .commit("012345abcdef"),
+            )
+            .unwrap()
+            .remove(0);
+
+        assert_eq!(commit.message, Some("Add commits endpoint\n".to_owned()));
+        assert_eq!(commit.author.as_ref().unwrap().to_string(), "Tester");
+        assert_eq!(commit.parents[0].hash, "abcdef012345");
+        assert_eq!(
+            commit.summary.unwrap().html,
+            Some("<p>Add commits endpoint</p>".to_owned())
+        );
+    }
+}
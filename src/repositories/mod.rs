@@ -1,25 +1,29 @@
 pub mod branchrestrictions;
+pub mod commits;
 pub mod pullrequest;
 pub mod pullrequests;
+pub mod refs;
 pub mod repository;
 
 use api;
 use api::BitBucketQuery;
+use query::{self, Direction, Query, Sort};
 
 use std::collections::HashMap;
-use url::percent_encoding::{utf8_percent_encode, DEFAULT_ENCODE_SET};
 
 #[derive(Clone, Debug)]
 pub struct RepositoriesBuilder {
     username: String,
-    filter: Option<String>,
+    query: Option<Query>,
+    sort: Option<Sort>,
 }
 
 impl RepositoriesBuilder {
     pub(crate) fn new(username: &str) -> RepositoriesBuilder {
         RepositoriesBuilder {
             username: username.to_owned(),
-            filter: None,
+            query: None,
+            sort: None,
         }
     }
 
@@ -31,8 +35,20 @@ impl RepositoriesBuilder {
         repository::RepositoryBuilder::new(&self.username, &repo.slug)
     }
 
+    /// Only returns Repositories whose name contains filter, replaces any query
     pub fn filter(&mut self, filter: &str) -> &mut RepositoriesBuilder {
-        self.filter = Some(filter.to_owned());
+        self.query(query::field("name").contains(filter))
+    }
+
+    /// Only returns Repositories matching the query, see the query module
+    pub fn query(&mut self, query: Query) -> &mut RepositoriesBuilder {
+        self.query = Some(query);
+        self
+    }
+
+    /// Sorts the results by the given field, see the query module
+    pub fn sort(&mut self, field: &str, direction: Direction) -> &mut RepositoriesBuilder {
+        self.sort = Some(Sort::new(field, direction));
         self
     }
 }
@@ -40,15 +56,12 @@ impl RepositoriesBuilder {
 impl api::GetQueryBuilder for RepositoriesBuilder {
     type Item = Repository;
     fn get_query(&self) -> BitBucketQuery {
-        let url_path = if let Some(ref filter) = self.filter {
-            let filter = format!(r#"name~"{}""#, filter);
-            let iter = utf8_percent_encode(&filter, DEFAULT_ENCODE_SET);
-            let encoded: String = iter.collect();
-            format!(r#"repositories/{}?pagelen=75&q={}"#, self.username, encoded)
-        } else {
-            format!("repositories/{}?pagelen=75", self.username,)
-        };
-        BitBucketQuery::new(url_path)
+        let url_path = format!("repositories/{}?pagelen=75", self.username);
+        query::apply(
+            BitBucketQuery::new(url_path),
+            self.query.as_ref(),
+            self.sort.as_ref(),
+        )
     }
 }
 
@@ -58,11 +71,16 @@ impl api::GetQueryBuilder for RepositoriesBuilder {
 pub struct Repository {
     pub name: String,
     pub full_name: String,
+    #[serde(default)]
     pub description: String,
     pub uuid: String,
+    #[serde(default)]
     pub links: HashMap<String, api::Link>,
     pub slug: String,
     pub is_private: bool,
+    /// Only included when requested via the fields parameter, e.g. `+values.mainbranch`
+    #[serde(default)]
+    pub mainbranch: Option<api::Branch>,
 }
 
 impl api::HtmlLink for Repository {
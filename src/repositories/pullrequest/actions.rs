@@ -0,0 +1,256 @@
+use api;
+use pullrequests::{MergeStrategy, Participant, PullRequest};
+
+#[derive(Clone, Debug)]
+pub struct ApprovalBuilder {
+    pullrequest_path: String,
+}
+
+impl ApprovalBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> ApprovalBuilder {
+        ApprovalBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::PostQueryBuilder for ApprovalBuilder {
+    type Body = ();
+    type Item = Participant;
+    fn post_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/approve", self.pullrequest_path))
+    }
+    fn post_body(&self) -> Option<&()> {
+        None
+    }
+}
+
+impl api::DeleteQueryBuilder for ApprovalBuilder {
+    fn delete_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/approve", self.pullrequest_path))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestChangesBuilder {
+    pullrequest_path: String,
+}
+
+impl RequestChangesBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> RequestChangesBuilder {
+        RequestChangesBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::PostQueryBuilder for RequestChangesBuilder {
+    type Body = ();
+    type Item = Participant;
+    fn post_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/request-changes", self.pullrequest_path))
+    }
+    fn post_body(&self) -> Option<&()> {
+        None
+    }
+}
+
+impl api::DeleteQueryBuilder for RequestChangesBuilder {
+    fn delete_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/request-changes", self.pullrequest_path))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeclineBuilder {
+    pullrequest_path: String,
+    body: Decline,
+}
+
+impl DeclineBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> DeclineBuilder {
+        DeclineBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+            body: Decline { reason: None },
+        }
+    }
+
+    pub fn reason(&mut self, reason: &str) -> &mut DeclineBuilder {
+        self.body.reason = Some(reason.to_owned());
+        self
+    }
+}
+
+impl api::PostQueryBuilder for DeclineBuilder {
+    type Body = Decline;
+    type Item = PullRequest;
+    fn post_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/decline", self.pullrequest_path))
+    }
+    fn post_body(&self) -> Option<&Decline> {
+        Some(&self.body)
+    }
+}
+
+/// Request body for declining a Pullrequest, see DeclineBuilder
+#[derive(Debug, Clone, Serialize)]
+pub struct Decline {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MergeBuilder {
+    pullrequest_path: String,
+    body: Merge,
+}
+
+impl MergeBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> MergeBuilder {
+        MergeBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+            body: Merge {
+                bb_type: "pullrequest",
+                message: None,
+                close_source_branch: None,
+                merge_strategy: None,
+            },
+        }
+    }
+
+    /// Message of the merge commit, BitBucket generates one by default
+    pub fn message(&mut self, message: &str) -> &mut MergeBuilder {
+        self.body.message = Some(message.to_owned());
+        self
+    }
+
+    /// Overrides the close_source_branch setting of the Pullrequest
+    pub fn close_source_branch(&mut self, close: bool) -> &mut MergeBuilder {
+        self.body.close_source_branch = Some(close);
+        self
+    }
+
+    /// Strategy used for merging, defaults to the repository's default strategy
+    pub fn strategy(&mut self, strategy: MergeStrategy) -> &mut MergeBuilder {
+        self.body.merge_strategy = Some(strategy);
+        self
+    }
+}
+
+impl api::PostQueryBuilder for MergeBuilder {
+    type Body = Merge;
+    type Item = PullRequest;
+    fn post_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/merge", self.pullrequest_path))
+    }
+    fn post_body(&self) -> Option<&Merge> {
+        Some(&self.body)
+    }
+}
+
+/// Request body for merging a Pullrequest, see MergeBuilder
+#[derive(Debug, Clone, Serialize)]
+pub struct Merge {
+    #[serde(rename = "type")]
+    bb_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    close_source_branch: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    merge_strategy: Option<MergeStrategy>,
+}
+
+#[cfg(test)]
+mod tests {
+    use api::Api;
+    use pullrequests::tests::{PARTICIPANT_JSON, PULL_REQUEST_JSON};
+    use pullrequests::{MergeStrategy, ParticipantState};
+    use serde_json;
+    use serde_json::json;
+    use transport::{MemoryTransport, Method, Response};
+
+    const PR_URL: &str = "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests/42";
+
+    fn api(transport: &MemoryTransport) -> Api {
+        Api::with_transport("tester", "secret", transport.clone())
+    }
+
+    #[test]
+    fn approve_and_unapprove() {
+        let transport = MemoryTransport::new();
+        let url = format!("{}/approve", PR_URL);
+        transport
+            .respond(
+                Method::Post,
+                &url,
+                Response::new(200, PARTICIPANT_JSON.as_bytes()),
+            )
+            .respond(Method::Delete, &url, Response::new(204, b""));
+        let api = api(&transport);
+        let approval = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42)
+            .approve();
+
+        let participant = api.post(&approval).unwrap();
+        assert!(participant.approved);
+        assert_eq!(participant.state, Some(ParticipantState::Approved));
+
+        api.delete(&approval).unwrap();
+        assert_eq!(transport.requests()[0].body, None);
+    }
+
+    #[test]
+    fn merge_sends_strategy() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Post,
+            &format!("{}/merge", PR_URL),
+            Response::new(200, PULL_REQUEST_JSON.as_bytes()),
+        );
+        let api = api(&transport);
+        let mut merge = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42)
+            .merge();
+        merge
+            .strategy(MergeStrategy::Squash)
+            .message("Release 1.0")
+            .close_source_branch(false);
+
+        api.post(&merge).unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(transport.requests()[0].body.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "type": "pullrequest",
+                "message": "Release 1.0",
+                "close_source_branch": false,
+                "merge_strategy": "squash"
+            })
+        );
+    }
+
+    #[test]
+    fn merge_rejects_unknown_strategy() {
+        let transport = MemoryTransport::new();
+        let api = api(&transport);
+        let mut merge = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42)
+            .merge();
+        merge.strategy(MergeStrategy::Unknown);
+
+        assert!(api.post(&merge).is_err());
+        assert!(transport.requests().is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use api;
+use pullrequests::PullRequestState;
+use repositories::pullrequest::comments::Comment;
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde_json;
+
+#[derive(Clone, Debug)]
+pub struct ActivityBuilder {
+    pullrequest_path: String,
+}
+
+impl ActivityBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> ActivityBuilder {
+        ActivityBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::GetQueryBuilder for ActivityBuilder {
+    type Item = PullRequestActivity;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/activity", self.pullrequest_path))
+    }
+}
+
+/// BitBucket data structure representing a single event in the timeline of a Pullrequest
+#[derive(Debug, Clone)]
+pub enum PullRequestActivity {
+    /// The Pullrequest was opened or changed, e.g. by pushing to the source branch
+    Update(Update),
+    #[allow(missing_docs)]
+    Approval(Review),
+    #[allow(missing_docs)]
+    ChangesRequested(Review),
+    #[allow(missing_docs)]
+    Comment(Comment),
+    /// Any event unknown to this library, as returned by BitBucket
+    Other(serde_json::Value),
+}
+
+impl PullRequestActivity {
+    /// When the event happened, None for unknown events
+    pub fn date(&self) -> Option<&api::Timestamp> {
+        match *self {
+            PullRequestActivity::Update(ref update) => Some(&update.date),
+            PullRequestActivity::Approval(ref review)
+            | PullRequestActivity::ChangesRequested(ref review) => Some(&review.date),
+            PullRequestActivity::Comment(ref comment) => Some(&comment.created_on),
+            PullRequestActivity::Other(_) => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PullRequestActivity {
+    fn deserialize<D>(deserializer: D) -> Result<PullRequestActivity, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Each event is an object keyed by its kind, next to a reference to the Pullrequest
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let event = |key: &str| value.get(key).filter(|event| !event.is_null()).cloned();
+        let activity = if let Some(update) = event("update") {
+            PullRequestActivity::Update(from_event("update", update)?)
+        } else if let Some(approval) = event("approval") {
+            PullRequestActivity::Approval(from_event("approval", approval)?)
+        } else if let Some(review) = event("changes_requested") {
+            PullRequestActivity::ChangesRequested(from_event("changes_requested", review)?)
+        } else if let Some(comment) = event("comment") {
+            PullRequestActivity::Comment(from_event("comment", comment)?)
+        } else {
+            PullRequestActivity::Other(value)
+        };
+        Ok(activity)
+    }
+}
+
+/// Decodes a known event, naming its kind if it is malformed
+fn from_event<T, E>(kind: &str, event: serde_json::Value) -> Result<T, E>
+where
+    T: de::DeserializeOwned,
+    E: de::Error,
+{
+    serde_json::from_value(event).map_err(|e| E::custom(format!("{}: {}", kind, e)))
+}
+
+/// BitBucket data structure representing a change of a Pullrequest
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Update {
+    /// State of the Pullrequest after the update
+    pub state: PullRequestState,
+    pub author: api::User,
+    pub date: api::Timestamp,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub reason: Option<String>,
+}
+
+/// BitBucket data structure representing an approval or a request for changes
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Review {
+    pub user: api::User,
+    pub date: api::Timestamp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::Api;
+    use repositories::pullrequest::comments::tests::COMMENT_JSON;
+    use transport::{MemoryTransport, Method, Response};
+
+    const USER_JSON: &str = r#"{"username": "tester", "nickname": "tester", "display_name": "Tester", "uuid": "{1}", "links": {}}"#;
+
+    #[test]
+    fn deserializes_typed_events() {
+        let pull_request =
+            r#""pull_request": {"id": 42, "title": "Release 1.0", "type": "pullrequest"}"#;
+        let body = format!(
+            r#"{{"values": [
+                {{"update": {{"state": "OPEN", "author": {user}, "date": "2018-10-01T10:00:00+00:00",
+                    "title": "Release 1.0", "description": "", "reason": ""}}, {pr}}},
+                {{"approval": {{"user": {user}, "date": "2018-10-02T10:00:00+00:00"}}, {pr}}},
+                {{"changes_requested": {{"user": {user}, "date": "2018-10-02T11:00:00+00:00"}}, {pr}}},
+                {{"comment": {comment}, {pr}}},
+                {{"task": {{"id": 1}}, {pr}}}
+            ]}}"#,
+            user = USER_JSON,
+            pr = pull_request,
+            comment = COMMENT_JSON
+        );
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests/42/activity",
+            Response::new(200, body.as_bytes()),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        let activity = api
+            .get(
+                &api.repositories("team")
+                    .repo_slug("repo")
+                    .pullrequests()
+                    .id(42)
+                    .activity(),
+            )
+            .unwrap();
+
+        match activity[0] {
+            PullRequestActivity::Update(ref update) => assert_eq!(update.author.username, "tester"),
+            ref other => panic!("expected update, got {:?}", other),
+        }
+        match activity[1] {
+            PullRequestActivity::Approval(ref review) => assert_eq!(review.user.username, "tester"),
+            ref other => panic!("expected approval, got {:?}", other),
+        }
+        match activity[2] {
+            PullRequestActivity::ChangesRequested(_) => (),
+            ref other => panic!("expected changes requested, got {:?}", other),
+        }
+        match activity[3] {
+            PullRequestActivity::Comment(ref comment) => assert_eq!(comment.id, 7),
+            ref other => panic!("expected comment, got {:?}", other),
+        }
+        match activity[4] {
+            PullRequestActivity::Other(ref value) => assert!(value.get("task").is_some()),
+            ref other => panic!("expected unknown event, got {:?}", other),
+        }
+        assert!(activity[1].date().is_some());
+        assert!(activity[4].date().is_none());
+    }
+
+    #[test]
+    fn malformed_events_fail_the_page() {
+        let body = format!(
+            r#"{{"pagelen": 10, "values": [
+                {{"approval": {{"user": {user}, "date": "2018-10-02T10:00:00+00:00"}}}},
+                {{"approval": {{"user": {user}, "date": 5}}}}
+            ]}}"#,
+            user = USER_JSON
+        );
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests/42/activity",
+            Response::new(200, body.as_bytes()),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        let error = api
+            .get(
+                &api.repositories("team")
+                    .repo_slug("repo")
+                    .pullrequests()
+                    .id(42)
+                    .activity(),
+            )
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("at values[1]: approval: invalid type"));
+    }
+}
@@ -0,0 +1,324 @@
+use api;
+
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub struct CommentsBuilder {
+    pullrequest_path: String,
+}
+
+impl CommentsBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> CommentsBuilder {
+        CommentsBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+
+    pub fn id(&self, id: u64) -> CommentBuilder {
+        CommentBuilder::new(&self.url_path(), id)
+    }
+
+    pub fn comment(&self, comment: &Comment) -> CommentBuilder {
+        self.id(comment.id)
+    }
+
+    /// Prepares a new comment with the given markdown content, send it using Api::post.
+    pub fn create(&self, content: &str) -> CreateCommentBuilder {
+        CreateCommentBuilder::new(&self.url_path(), content)
+    }
+
+    fn url_path(&self) -> String {
+        format!("{}/comments", self.pullrequest_path)
+    }
+}
+
+impl api::GetQueryBuilder for CommentsBuilder {
+    type Item = Comment;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path())
+    }
+}
+
+/// Single comment, fetched using Api::get and removed using Api::delete
+#[derive(Clone, Debug)]
+pub struct CommentBuilder {
+    comments_path: String,
+    id: u64,
+}
+
+impl CommentBuilder {
+    pub(crate) fn new(comments_path: &str, id: u64) -> CommentBuilder {
+        CommentBuilder {
+            comments_path: comments_path.to_owned(),
+            id,
+        }
+    }
+
+    /// Prepares replacing the content of the comment, send it using Api::put.
+    pub fn edit(&self, content: &str) -> EditCommentBuilder {
+        EditCommentBuilder {
+            comment_path: self.url_path(),
+            body: CommentBody::new(content),
+        }
+    }
+
+    /// Prepares a reply to the comment, send it using Api::post.
+    pub fn reply(&self, content: &str) -> CreateCommentBuilder {
+        let mut reply = CreateCommentBuilder::new(&self.comments_path, content);
+        reply.reply_to(self.id);
+        reply
+    }
+
+    fn url_path(&self) -> String {
+        format!("{}/{}", self.comments_path, self.id)
+    }
+}
+
+impl api::GetQueryBuilder for CommentBuilder {
+    type Item = Comment;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path())
+    }
+}
+
+impl api::DeleteQueryBuilder for CommentBuilder {
+    fn delete_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CreateCommentBuilder {
+    comments_path: String,
+    body: CommentBody,
+}
+
+impl CreateCommentBuilder {
+    pub(crate) fn new(comments_path: &str, content: &str) -> CreateCommentBuilder {
+        CreateCommentBuilder {
+            comments_path: comments_path.to_owned(),
+            body: CommentBody::new(content),
+        }
+    }
+
+    /// Posts the comment as reply to the comment with the given id
+    pub fn reply_to(&mut self, parent_id: u64) -> &mut CreateCommentBuilder {
+        self.body.parent = Some(CommentRef { id: parent_id });
+        self
+    }
+
+    /// Anchors the comment to a line of the file's new version
+    pub fn inline(&mut self, path: &str, line: u32) -> &mut CreateCommentBuilder {
+        self.body.inline = Some(Inline {
+            path: path.to_owned(),
+            from: None,
+            to: Some(line),
+        });
+        self
+    }
+
+    /// Anchors the comment to a line of the file's old version, e.g. a removed line
+    pub fn inline_old(&mut self, path: &str, line: u32) -> &mut CreateCommentBuilder {
+        self.body.inline = Some(Inline {
+            path: path.to_owned(),
+            from: Some(line),
+            to: None,
+        });
+        self
+    }
+}
+
+impl api::PostQueryBuilder for CreateCommentBuilder {
+    type Body = CommentBody;
+    type Item = Comment;
+    fn post_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.comments_path.to_owned())
+    }
+    fn post_body(&self) -> Option<&CommentBody> {
+        Some(&self.body)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EditCommentBuilder {
+    comment_path: String,
+    body: CommentBody,
+}
+
+impl api::PutQueryBuilder for EditCommentBuilder {
+    type Body = CommentBody;
+    type Item = Comment;
+    fn put_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.comment_path.to_owned())
+    }
+    fn put_body(&self) -> &CommentBody {
+        &self.body
+    }
+}
+
+/// Request body for creating or editing a Comment
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentBody {
+    content: RawContent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<CommentRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inline: Option<Inline>,
+}
+
+impl CommentBody {
+    fn new(content: &str) -> CommentBody {
+        CommentBody {
+            content: RawContent {
+                raw: content.to_owned(),
+            },
+            parent: None,
+            inline: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RawContent {
+    raw: String,
+}
+
+/// BitBucket data structure representing a single comment on a Pullrequest
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Comment {
+    pub id: u64,
+    pub content: api::Rendered,
+    pub user: api::User,
+    /// The comment this comment replies to
+    pub parent: Option<CommentRef>,
+    /// The file and line the comment is anchored to, None for general comments
+    pub inline: Option<Inline>,
+    pub created_on: api::Timestamp,
+    pub updated_on: Option<api::Timestamp>,
+    #[serde(default)]
+    pub deleted: bool,
+    pub links: HashMap<String, api::Link>,
+}
+
+impl api::HtmlLink for Comment {
+    fn links(&self) -> &HashMap<String, api::Link> {
+        &self.links
+    }
+}
+
+/// Reference to another Comment by its id
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct CommentRef {
+    pub id: u64,
+}
+
+/// BitBucket data structure anchoring a Comment to a file.
+/// `to` refers to a line of the new version of the file, `from` to a line of the old version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct Inline {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<u32>,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use api::Api;
+    use serde_json;
+    use serde_json::json;
+    use transport::{MemoryTransport, Method, Response};
+
+    const COMMENTS_URL: &str =
+        "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests/42/comments";
+
+    /// Inline reply as returned by BitBucket, shared with the activity tests
+    pub(crate) const COMMENT_JSON: &str = r#"{
+        "id": 7,
+        "content": {"type": "rendered", "raw": "Typo", "markup": "markdown", "html": "<p>Typo</p>"},
+        "user": {"username": "tester", "nickname": "tester", "display_name": "Tester", "uuid": "{1}", "links": {}},
+        "parent": {"id": 3, "links": {}},
+        "inline": {"path": "src/lib.rs", "from": null, "to": 12},
+        "created_on": "2018-10-02T13:27:15.456178+00:00",
+        "updated_on": "2018-10-02T13:27:15.456178+00:00",
+        "deleted": false,
+        "links": {}
+    }"#;
+
+    fn comments(api: &Api) -> CommentsBuilder {
+        api.repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42)
+            .comments()
+    }
+
+    #[test]
+    fn lists_comments() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            COMMENTS_URL,
+            Response::new(
+                200,
+                format!(r#"{{"values": [{}]}}"#, COMMENT_JSON).as_bytes(),
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        let comments = api.get(&comments(&api)).unwrap();
+
+        assert_eq!(comments[0].content.raw, "Typo");
+        assert_eq!(comments[0].parent, Some(CommentRef { id: 3 }));
+        assert_eq!(comments[0].inline.as_ref().unwrap().to, Some(12));
+    }
+
+    #[test]
+    fn replies_inline() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Post,
+            COMMENTS_URL,
+            Response::new(201, COMMENT_JSON.as_bytes()),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+
+        let mut reply = comments(&api).id(3).reply("Typo");
+        reply.inline("src/lib.rs", 12);
+        api.post(&reply).unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(transport.requests()[0].body.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "content": {"raw": "Typo"},
+                "parent": {"id": 3},
+                "inline": {"path": "src/lib.rs", "to": 12}
+            })
+        );
+    }
+
+    #[test]
+    fn edits_and_deletes() {
+        let transport = MemoryTransport::new();
+        let url = format!("{}/7", COMMENTS_URL);
+        transport
+            .respond(
+                Method::Put,
+                &url,
+                Response::new(200, COMMENT_JSON.as_bytes()),
+            )
+            .respond(Method::Delete, &url, Response::new(204, b""));
+        let api = Api::with_transport("tester", "secret", transport);
+        let comment = comments(&api).id(7);
+
+        assert!(api.put(&comment.edit("Typo")).is_ok());
+        assert!(api.delete(&comment).is_ok());
+    }
+}
@@ -0,0 +1,21 @@
+use api;
+
+#[derive(Clone, Debug)]
+pub struct CommitsBuilder {
+    pullrequest_path: String,
+}
+
+impl CommitsBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> CommitsBuilder {
+        CommitsBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::GetQueryBuilder for CommitsBuilder {
+    type Item = api::Commit;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/commits", self.pullrequest_path))
+    }
+}
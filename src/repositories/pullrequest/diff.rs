@@ -0,0 +1,181 @@
+use api;
+
+#[derive(Clone, Debug)]
+pub struct DiffBuilder {
+    pullrequest_path: String,
+}
+
+impl DiffBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> DiffBuilder {
+        DiffBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::RawQueryBuilder for DiffBuilder {
+    fn raw_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/diff", self.pullrequest_path))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PatchBuilder {
+    pullrequest_path: String,
+}
+
+impl PatchBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> PatchBuilder {
+        PatchBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::RawQueryBuilder for PatchBuilder {
+    fn raw_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/patch", self.pullrequest_path))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DiffStatBuilder {
+    pullrequest_path: String,
+}
+
+impl DiffStatBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> DiffStatBuilder {
+        DiffStatBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::GetQueryBuilder for DiffStatBuilder {
+    type Item = DiffStat;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/diffstat", self.pullrequest_path))
+    }
+}
+
+/// BitBucket data structure representing the changes to a single file
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct DiffStat {
+    pub status: DiffStatus,
+    pub lines_added: u32,
+    pub lines_removed: u32,
+    /// The file before the change, None if it was added
+    pub old: Option<CommitFile>,
+    /// The file after the change, None if it was removed
+    pub new: Option<CommitFile>,
+}
+
+impl DiffStat {
+    /// Path of the file before the change
+    pub fn old_path(&self) -> Option<&str> {
+        self.old.as_ref().map(|file| file.path.as_str())
+    }
+
+    /// Path of the file after the change
+    pub fn new_path(&self) -> Option<&str> {
+        self.new.as_ref().map(|file| file.path.as_str())
+    }
+}
+
+/// BitBucket data structure representing a file at a specific commit
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct CommitFile {
+    pub path: String,
+}
+
+/// BitBucket data structure representing the kind of change to a file
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffStatus {
+    #[allow(missing_docs)]
+    Added,
+    #[allow(missing_docs)]
+    Removed,
+    #[allow(missing_docs)]
+    Modified,
+    #[allow(missing_docs)]
+    Renamed,
+    /// Any status unknown to this library, e.g. merge conflicts
+    #[serde(other)]
+    Unknown,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::Api;
+    use transport::{MemoryTransport, Method, Response};
+
+    const PULLREQUEST_URL: &str =
+        "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests/42";
+
+    #[test]
+    fn deserializes_diffstat() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            &format!("{}/diffstat", PULLREQUEST_URL),
+            Response::new(
+                200,
+                br#"{"values": [
+                    {"type": "diffstat", "status": "renamed", "lines_added": 3, "lines_removed": 1,
+                     "old": {"path": "src/old.rs", "type": "commit_file"},
+                     "new": {"path": "src/new.rs", "type": "commit_file"}},
+                    {"type": "diffstat", "status": "added", "lines_added": 10, "lines_removed": 0,
+                     "old": null, "new": {"path": "README.md", "type": "commit_file"}},
+                    {"type": "diffstat", "status": "merge conflict", "lines_added": 0, "lines_removed": 0,
+                     "old": null, "new": null}
+                ]}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+        let query = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42);
+
+        let diffstat = api.get(&query.diffstat()).unwrap();
+
+        assert_eq!(diffstat[0].status, DiffStatus::Renamed);
+        assert_eq!(diffstat[0].old_path(), Some("src/old.rs"));
+        assert_eq!(diffstat[0].new_path(), Some("src/new.rs"));
+        assert_eq!(diffstat[1].old_path(), None);
+        assert_eq!(diffstat[1].lines_added, 10);
+        assert_eq!(diffstat[2].status, DiffStatus::Unknown);
+    }
+
+    #[test]
+    fn fetches_diff_as_text() {
+        let diff = "diff --git a/README.md b/README.md\n+Hello\n";
+        let transport = MemoryTransport::new();
+        transport
+            .respond(
+                Method::Get,
+                &format!("{}/diff", PULLREQUEST_URL),
+                Response::new(200, diff.as_bytes()),
+            )
+            .respond(
+                Method::Get,
+                &format!("{}/patch", PULLREQUEST_URL),
+                Response::new(200, b"From 012345abcdef"),
+            );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        let query = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42);
+
+        assert_eq!(api.get_raw(&query.diff()).unwrap(), diff);
+        assert_eq!(api.get_raw(&query.patch()).unwrap(), "From 012345abcdef");
+        assert_eq!(transport.requests()[0].header("Accept"), Some("text/plain"));
+    }
+}
@@ -0,0 +1,124 @@
+pub mod actions;
+pub mod activity;
+pub mod comments;
+pub mod commits;
+pub mod diff;
+pub mod statuses;
+
+use api;
+use pullrequests::PullRequest;
+
+#[derive(Clone, Debug)]
+pub struct PullrequestBuilder {
+    username: String,
+    repo_slug: String,
+    id: u64,
+}
+
+impl PullrequestBuilder {
+    pub(crate) fn new(username: &str, repo_slug: &str, id: u64) -> PullrequestBuilder {
+        PullrequestBuilder {
+            username: username.to_owned(),
+            repo_slug: repo_slug.to_owned(),
+            id,
+        }
+    }
+
+    /// Approve the Pullrequest using Api::post, withdraw the approval using Api::delete
+    pub fn approve(&self) -> actions::ApprovalBuilder {
+        actions::ApprovalBuilder::new(&self.url_path())
+    }
+
+    /// Request changes using Api::post, withdraw the request using Api::delete
+    pub fn request_changes(&self) -> actions::RequestChangesBuilder {
+        actions::RequestChangesBuilder::new(&self.url_path())
+    }
+
+    /// Decline the Pullrequest using Api::post
+    pub fn decline(&self) -> actions::DeclineBuilder {
+        actions::DeclineBuilder::new(&self.url_path())
+    }
+
+    /// Merge the Pullrequest using Api::post
+    pub fn merge(&self) -> actions::MergeBuilder {
+        actions::MergeBuilder::new(&self.url_path())
+    }
+
+    /// Timeline of updates, approvals, requests for changes and comments on the Pullrequest
+    pub fn activity(&self) -> activity::ActivityBuilder {
+        activity::ActivityBuilder::new(&self.url_path())
+    }
+
+    /// Comments on the Pullrequest
+    pub fn comments(&self) -> comments::CommentsBuilder {
+        comments::CommentsBuilder::new(&self.url_path())
+    }
+
+    /// Unified diff of the changes, fetched as text using Api::get_raw
+    pub fn diff(&self) -> diff::DiffBuilder {
+        diff::DiffBuilder::new(&self.url_path())
+    }
+
+    /// Changed files with the number of added and removed lines
+    pub fn diffstat(&self) -> diff::DiffStatBuilder {
+        diff::DiffStatBuilder::new(&self.url_path())
+    }
+
+    /// Commits as patch series, fetched as text using Api::get_raw
+    pub fn patch(&self) -> diff::PatchBuilder {
+        diff::PatchBuilder::new(&self.url_path())
+    }
+
+    /// Commits on the source branch of the Pullrequest
+    pub fn commits(&self) -> commits::CommitsBuilder {
+        commits::CommitsBuilder::new(&self.url_path())
+    }
+
+    /// Build statuses of the commits on the source branch of the Pullrequest
+    pub fn statuses(&self) -> statuses::StatusesBuilder {
+        statuses::StatusesBuilder::new(&self.url_path())
+    }
+
+    /// Path of the Pullrequest, shared by all Pullrequest sub resources
+    fn url_path(&self) -> String {
+        format!(
+            "repositories/{}/{}/pullrequests/{}",
+            self.username, self.repo_slug, self.id
+        )
+    }
+}
+
+impl api::GetQueryBuilder for PullrequestBuilder {
+    type Item = PullRequest;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use api::Api;
+    use pullrequests::tests::PULL_REQUEST_JSON;
+    use transport::{MemoryTransport, Method, Response};
+
+    #[test]
+    fn gets_single_pull_request() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests/42",
+            Response::new(200, PULL_REQUEST_JSON.as_bytes()),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+        let pr = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42);
+
+        let pullrequest = api.get(&pr).unwrap();
+
+        assert_eq!(pullrequest.len(), 1);
+        assert_eq!(pullrequest[0].title, "Release 1.0");
+    }
+}
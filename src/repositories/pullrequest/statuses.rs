@@ -0,0 +1,114 @@
+use api;
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Debug)]
+pub struct StatusesBuilder {
+    pullrequest_path: String,
+}
+
+impl StatusesBuilder {
+    pub(crate) fn new(pullrequest_path: &str) -> StatusesBuilder {
+        StatusesBuilder {
+            pullrequest_path: pullrequest_path.to_owned(),
+        }
+    }
+}
+
+impl api::GetQueryBuilder for StatusesBuilder {
+    type Item = CommitStatus;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(format!("{}/statuses", self.pullrequest_path))
+    }
+}
+
+/// BitBucket data structure representing the build status reported for a commit
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct CommitStatus {
+    pub key: String,
+    pub state: CommitStatusState,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub url: String,
+    pub refname: Option<String>,
+    pub created_on: api::Timestamp,
+    pub updated_on: api::Timestamp,
+    pub links: HashMap<String, api::Link>,
+}
+
+impl api::HtmlLink for CommitStatus {
+    fn links(&self) -> &HashMap<String, api::Link> {
+        &self.links
+    }
+}
+
+/// BitBucket data structure representing all possible states of a CommitStatus
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CommitStatusState {
+    #[allow(missing_docs)]
+    Successful,
+    #[allow(missing_docs)]
+    Failed,
+    #[allow(missing_docs)]
+    InProgress,
+    #[allow(missing_docs)]
+    Stopped,
+    /// Any state unknown to this library
+    #[serde(other)]
+    Unknown,
+}
+
+impl fmt::Display for CommitStatusState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CommitStatusState::Successful => write!(f, "Successful"),
+            CommitStatusState::Failed => write!(f, "Failed"),
+            CommitStatusState::InProgress => write!(f, "In Progress"),
+            CommitStatusState::Stopped => write!(f, "Stopped"),
+            CommitStatusState::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::Api;
+    use transport::{MemoryTransport, Method, Response};
+
+    #[test]
+    fn lists_pullrequest_statuses() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests/42/statuses",
+            Response::new(
+                200,
+                br#"{"values": [
+                    {"key": "ci", "state": "INPROGRESS", "name": "Build #1", "description": null,
+                     "url": "https://ci/1", "refname": null, "links": {},
+                     "created_on": "2018-10-02T13:27:15.456178+00:00",
+                     "updated_on": "2018-10-02T13:27:15.456178+00:00"},
+                    {"key": "lint", "state": "QUEUED", "name": null, "description": null,
+                     "url": "https://ci/2", "refname": null, "links": {},
+                     "created_on": "2018-10-02T13:27:15.456178+00:00",
+                     "updated_on": "2018-10-02T13:27:15.456178+00:00"}
+                ]}"#,
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+        let pr = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .id(42);
+
+        let statuses = api.get(&pr.statuses()).unwrap();
+
+        assert_eq!(statuses[0].state, CommitStatusState::InProgress);
+        assert_eq!(statuses[1].state, CommitStatusState::Unknown);
+    }
+}
@@ -1,11 +1,16 @@
 use api;
-use pullrequests::PullRequest;
+use pullrequests::{self, PullRequest, PullRequestState};
+use query::{self, Direction, Query, Sort};
 use repositories::pullrequest;
+use users::User;
 
 #[derive(Clone, Debug)]
 pub struct PullrequestsBuilder {
     username: String,
     repo_slug: String,
+    states: Vec<PullRequestState>,
+    query: Option<Query>,
+    sort: Option<Sort>,
 }
 
 impl PullrequestsBuilder {
@@ -13,12 +18,40 @@ impl PullrequestsBuilder {
         PullrequestsBuilder {
             username: username.to_owned(),
             repo_slug: repo_slug.to_owned(),
+            states: Vec::new(),
+            query: None,
+            sort: None,
         }
     }
 
-    pub fn id(&self, id: &str) -> pullrequest::PullrequestBuilder {
+    /// Only returns Pullrequests in the given state, may be called multiple times.
+    /// BitBucket returns only open Pullrequests unless a state is given.
+    pub fn state(&mut self, state: PullRequestState) -> &mut PullrequestsBuilder {
+        self.states.push(state);
+        self
+    }
+
+    /// Only returns Pullrequests matching the query, see the query module
+    pub fn query(&mut self, query: Query) -> &mut PullrequestsBuilder {
+        self.query = Some(query);
+        self
+    }
+
+    /// Sorts the results by the given field, see the query module
+    pub fn sort(&mut self, field: &str, direction: Direction) -> &mut PullrequestsBuilder {
+        self.sort = Some(Sort::new(field, direction));
+        self
+    }
+
+    pub fn id(&self, id: u64) -> pullrequest::PullrequestBuilder {
         pullrequest::PullrequestBuilder::new(&self.username, &self.repo_slug, id)
     }
+
+    /// Prepares a new pull request merging source_branch into the repository's main branch,
+    /// send it using Api::post.
+    pub fn create(&self, title: &str, source_branch: &str) -> CreatePullrequestBuilder {
+        CreatePullrequestBuilder::new(&self.username, &self.repo_slug, title, source_branch)
+    }
 }
 
 impl api::GetQueryBuilder for PullrequestsBuilder {
@@ -28,6 +61,180 @@ impl api::GetQueryBuilder for PullrequestsBuilder {
             r#"repositories/{}/{}/pullrequests?pagelen=50"#,
             self.username, self.repo_slug
         );
+        let url_path = pullrequests::with_states(api::BitBucketQuery::new(url_path), &self.states);
+        query::apply(url_path, self.query.as_ref(), self.sort.as_ref())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CreatePullrequestBuilder {
+    username: String,
+    repo_slug: String,
+    body: NewPullRequest,
+}
+
+impl CreatePullrequestBuilder {
+    pub(crate) fn new(
+        username: &str,
+        repo_slug: &str,
+        title: &str,
+        source_branch: &str,
+    ) -> CreatePullrequestBuilder {
+        CreatePullrequestBuilder {
+            username: username.to_owned(),
+            repo_slug: repo_slug.to_owned(),
+            body: NewPullRequest {
+                title: title.to_owned(),
+                description: None,
+                source: BranchTarget::new(source_branch),
+                destination: None,
+                reviewers: Vec::new(),
+                close_source_branch: None,
+            },
+        }
+    }
+
+    /// Description of the Pullrequest, may contain markdown
+    pub fn description(&mut self, description: &str) -> &mut CreatePullrequestBuilder {
+        self.body.description = Some(description.to_owned());
+        self
+    }
+
+    /// Branch to merge into, defaults to the repository's main branch
+    pub fn destination_branch(&mut self, branch: &str) -> &mut CreatePullrequestBuilder {
+        self.body.destination = Some(BranchTarget::new(branch));
+        self
+    }
+
+    /// Requests a review from the given User, may be called multiple times
+    pub fn reviewer(&mut self, user: &User) -> &mut CreatePullrequestBuilder {
+        self.reviewer_uuid(&user.uuid)
+    }
+
+    /// Requests a review from the User with the given uuid, may be called multiple times
+    pub fn reviewer_uuid(&mut self, uuid: &str) -> &mut CreatePullrequestBuilder {
+        self.body.reviewers.push(Reviewer {
+            uuid: uuid.to_owned(),
+        });
+        self
+    }
+
+    /// Deletes the source branch once the Pullrequest is merged
+    pub fn close_source_branch(&mut self, close: bool) -> &mut CreatePullrequestBuilder {
+        self.body.close_source_branch = Some(close);
+        self
+    }
+}
+
+impl api::PostQueryBuilder for CreatePullrequestBuilder {
+    type Body = NewPullRequest;
+    type Item = PullRequest;
+    fn post_query(&self) -> api::BitBucketQuery {
+        let url_path = format!(
+            "repositories/{}/{}/pullrequests",
+            self.username, self.repo_slug
+        );
         api::BitBucketQuery::new(url_path)
     }
+    fn post_body(&self) -> Option<&NewPullRequest> {
+        Some(&self.body)
+    }
+}
+
+/// Request body for creating a Pullrequest, see CreatePullrequestBuilder
+#[derive(Debug, Clone, Serialize)]
+pub struct NewPullRequest {
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    source: BranchTarget,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<BranchTarget>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    reviewers: Vec<Reviewer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    close_source_branch: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BranchTarget {
+    branch: BranchName,
+}
+
+impl BranchTarget {
+    fn new(name: &str) -> BranchTarget {
+        BranchTarget {
+            branch: BranchName {
+                name: name.to_owned(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BranchName {
+    name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Reviewer {
+    uuid: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use api::{Api, GetQueryBuilder, PullRequestState};
+    use pullrequests::tests::PULL_REQUEST_JSON;
+    use serde_json;
+    use serde_json::json;
+    use transport::{MemoryTransport, Method, Response};
+
+    #[test]
+    fn create_posts_new_pull_request() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Post,
+            "https://api.bitbucket.org/2.0/repositories/team/repo/pullrequests",
+            Response::new(201, PULL_REQUEST_JSON.as_bytes()),
+        );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+
+        let mut create = api
+            .repositories("team")
+            .repo_slug("repo")
+            .pullrequests()
+            .create("Release 1.0", "release/1.0");
+        create
+            .description("Release")
+            .destination_branch("master")
+            .reviewer_uuid("{3}")
+            .close_source_branch(true);
+        let pr = api.post(&create).unwrap();
+
+        assert_eq!(pr.id, 42);
+        let body: serde_json::Value =
+            serde_json::from_slice(transport.requests()[0].body.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "title": "Release 1.0",
+                "description": "Release",
+                "source": {"branch": {"name": "release/1.0"}},
+                "destination": {"branch": {"name": "master"}},
+                "reviewers": [{"uuid": "{3}"}],
+                "close_source_branch": true
+            })
+        );
+    }
+
+    #[test]
+    fn filters_by_state() {
+        let api = Api::new("tester", "secret");
+        let mut query = api.repositories("team").repo_slug("repo").pullrequests();
+        query.state(PullRequestState::Merged);
+        assert_eq!(
+            query.get_query().get_url(""),
+            "repositories/team/repo/pullrequests?pagelen=50&state=MERGED"
+        );
+    }
 }
@@ -0,0 +1,468 @@
+use api;
+use query::{self, Direction, Query, Sort};
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde_json;
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+
+#[derive(Clone, Debug)]
+pub struct RefsBuilder {
+    repository_path: String,
+    query: Option<Query>,
+    sort: Option<Sort>,
+}
+
+impl RefsBuilder {
+    pub(crate) fn new(repository_path: &str) -> RefsBuilder {
+        RefsBuilder {
+            repository_path: repository_path.to_owned(),
+            query: None,
+            sort: None,
+        }
+    }
+
+    /// Only returns refs matching the query, see the query module
+    pub fn query(&mut self, query: Query) -> &mut RefsBuilder {
+        self.query = Some(query);
+        self
+    }
+
+    /// Sorts the results by the given field, see the query module
+    pub fn sort(&mut self, field: &str, direction: Direction) -> &mut RefsBuilder {
+        self.sort = Some(Sort::new(field, direction));
+        self
+    }
+}
+
+impl api::GetQueryBuilder for RefsBuilder {
+    type Item = Ref;
+    fn get_query(&self) -> api::BitBucketQuery {
+        let url_path = format!("{}/refs", self.repository_path);
+        query::apply(
+            api::BitBucketQuery::new(url_path),
+            self.query.as_ref(),
+            self.sort.as_ref(),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BranchesBuilder {
+    repository_path: String,
+    query: Option<Query>,
+    sort: Option<Sort>,
+}
+
+impl BranchesBuilder {
+    pub(crate) fn new(repository_path: &str) -> BranchesBuilder {
+        BranchesBuilder {
+            repository_path: repository_path.to_owned(),
+            query: None,
+            sort: None,
+        }
+    }
+
+    pub fn name(&self, name: &str) -> BranchBuilder {
+        BranchBuilder {
+            url_path: ref_path(&self.url_path(), name),
+        }
+    }
+
+    /// Only returns branches matching the query, see the query module
+    pub fn query(&mut self, query: Query) -> &mut BranchesBuilder {
+        self.query = Some(query);
+        self
+    }
+
+    /// Sorts the results by the given field, see the query module
+    pub fn sort(&mut self, field: &str, direction: Direction) -> &mut BranchesBuilder {
+        self.sort = Some(Sort::new(field, direction));
+        self
+    }
+
+    /// Prepares a new branch pointing to the given commit, send it using Api::post.
+    pub fn create(&self, name: &str, target_hash: &str) -> CreateRefBuilder<api::Branch> {
+        CreateRefBuilder::new(&self.url_path(), name, target_hash)
+    }
+
+    fn url_path(&self) -> String {
+        format!("{}/refs/branches", self.repository_path)
+    }
+}
+
+impl api::GetQueryBuilder for BranchesBuilder {
+    type Item = api::Branch;
+    fn get_query(&self) -> api::BitBucketQuery {
+        query::apply(
+            api::BitBucketQuery::new(self.url_path()),
+            self.query.as_ref(),
+            self.sort.as_ref(),
+        )
+    }
+}
+
+/// Single branch, fetched using Api::get and removed using Api::delete
+#[derive(Clone, Debug)]
+pub struct BranchBuilder {
+    url_path: String,
+}
+
+impl api::GetQueryBuilder for BranchBuilder {
+    type Item = api::Branch;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+}
+
+impl api::DeleteQueryBuilder for BranchBuilder {
+    fn delete_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TagsBuilder {
+    repository_path: String,
+    query: Option<Query>,
+    sort: Option<Sort>,
+}
+
+impl TagsBuilder {
+    pub(crate) fn new(repository_path: &str) -> TagsBuilder {
+        TagsBuilder {
+            repository_path: repository_path.to_owned(),
+            query: None,
+            sort: None,
+        }
+    }
+
+    pub fn name(&self, name: &str) -> TagBuilder {
+        TagBuilder {
+            url_path: ref_path(&self.url_path(), name),
+        }
+    }
+
+    /// Only returns tags matching the query, see the query module
+    pub fn query(&mut self, query: Query) -> &mut TagsBuilder {
+        self.query = Some(query);
+        self
+    }
+
+    /// Sorts the results by the given field, see the query module
+    pub fn sort(&mut self, field: &str, direction: Direction) -> &mut TagsBuilder {
+        self.sort = Some(Sort::new(field, direction));
+        self
+    }
+
+    /// Prepares a new tag pointing to the given commit, send it using Api::post.
+    pub fn create(&self, name: &str, target_hash: &str) -> CreateRefBuilder<Tag> {
+        CreateRefBuilder::new(&self.url_path(), name, target_hash)
+    }
+
+    fn url_path(&self) -> String {
+        format!("{}/refs/tags", self.repository_path)
+    }
+}
+
+impl api::GetQueryBuilder for TagsBuilder {
+    type Item = Tag;
+    fn get_query(&self) -> api::BitBucketQuery {
+        query::apply(
+            api::BitBucketQuery::new(self.url_path()),
+            self.query.as_ref(),
+            self.sort.as_ref(),
+        )
+    }
+}
+
+/// Single tag, fetched using Api::get and removed using Api::delete
+#[derive(Clone, Debug)]
+pub struct TagBuilder {
+    url_path: String,
+}
+
+impl api::GetQueryBuilder for TagBuilder {
+    type Item = Tag;
+    fn get_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+}
+
+impl api::DeleteQueryBuilder for TagBuilder {
+    fn delete_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+}
+
+/// Creates a branch or tag, answered with the created Item
+#[derive(Clone, Debug)]
+pub struct CreateRefBuilder<T> {
+    url_path: String,
+    body: NewRef,
+    item: PhantomData<T>,
+}
+
+impl<T> CreateRefBuilder<T> {
+    fn new(url_path: &str, name: &str, target_hash: &str) -> CreateRefBuilder<T> {
+        CreateRefBuilder {
+            url_path: url_path.to_owned(),
+            body: NewRef {
+                name: name.to_owned(),
+                target: Target {
+                    hash: target_hash.to_owned(),
+                },
+                message: None,
+            },
+            item: PhantomData,
+        }
+    }
+}
+
+impl CreateRefBuilder<Tag> {
+    /// Creates an annotated tag with the given message
+    pub fn message(&mut self, message: &str) -> &mut CreateRefBuilder<Tag> {
+        self.body.message = Some(message.to_owned());
+        self
+    }
+}
+
+impl<T> api::PostQueryBuilder for CreateRefBuilder<T>
+where
+    T: ::serde::de::DeserializeOwned + fmt::Debug,
+{
+    type Body = NewRef;
+    type Item = T;
+    fn post_query(&self) -> api::BitBucketQuery {
+        api::BitBucketQuery::new(self.url_path.to_owned())
+    }
+    fn post_body(&self) -> Option<&NewRef> {
+        Some(&self.body)
+    }
+}
+
+/// Request body for creating a branch or tag
+#[derive(Debug, Clone, Serialize)]
+pub struct NewRef {
+    name: String,
+    target: Target,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Target {
+    hash: String,
+}
+
+/// Appends the name as single path segment, slashes in names are encoded as well
+pub(crate) fn ref_path(refs_path: &str, name: &str) -> String {
+    let name: String = utf8_percent_encode(name, PATH_SEGMENT_ENCODE_SET).collect();
+    format!("{}/{}", refs_path, name)
+}
+
+/// BitBucket data structure representing a branch or tag, see `RepositoryBuilder::refs`
+#[derive(Debug, Clone)]
+pub enum Ref {
+    #[allow(missing_docs)]
+    Branch(Box<api::Branch>),
+    #[allow(missing_docs)]
+    Tag(Box<Tag>),
+    /// Any ref type unknown to this library, as returned by BitBucket
+    Other(serde_json::Value),
+}
+
+impl Ref {
+    /// Name of the branch or tag, empty for unknown refs without name
+    pub fn name(&self) -> &str {
+        match *self {
+            Ref::Branch(ref branch) => &branch.name,
+            Ref::Tag(ref tag) => &tag.name,
+            Ref::Other(ref value) => value
+                .get("name")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or(""),
+        }
+    }
+
+    /// The commit the branch or tag points to, None for unknown refs
+    pub fn target(&self) -> Option<&api::Commit> {
+        match *self {
+            Ref::Branch(ref branch) => branch.target.as_ref(),
+            Ref::Tag(ref tag) => Some(&tag.target),
+            Ref::Other(_) => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Ref {
+    fn deserialize<D>(deserializer: D) -> Result<Ref, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let kind = value
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_owned);
+        let reference = match kind.as_deref() {
+            Some("branch") => Ref::Branch(from_ref("branch", value)?),
+            Some("tag") => Ref::Tag(from_ref("tag", value)?),
+            _ => Ref::Other(value),
+        };
+        Ok(reference)
+    }
+}
+
+/// Decodes a known ref, naming its type if it is malformed
+fn from_ref<T, E>(kind: &str, reference: serde_json::Value) -> Result<T, E>
+where
+    T: de::DeserializeOwned,
+    E: de::Error,
+{
+    serde_json::from_value(reference).map_err(|e| E::custom(format!("{}: {}", kind, e)))
+}
+
+/// BitBucket data structure representing a single tag
+#[derive(Debug, Clone, Deserialize)]
+#[allow(missing_docs)]
+pub struct Tag {
+    pub name: String,
+    /// The tagged commit
+    pub target: api::Commit,
+    /// Message of annotated tags
+    pub message: Option<String>,
+    /// When an annotated tag was created
+    pub date: Option<api::Timestamp>,
+    /// Who created an annotated tag
+    pub tagger: Option<api::Author>,
+    #[serde(default)]
+    pub links: HashMap<String, api::Link>,
+}
+
+impl api::HtmlLink for Tag {
+    fn links(&self) -> &HashMap<String, api::Link> {
+        &self.links
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api::{Api, MergeStrategy};
+    use serde_json;
+    use serde_json::json;
+    use transport::{MemoryTransport, Method, Response};
+
+    const REFS_URL: &str = "https://api.bitbucket.org/2.0/repositories/team/repo/refs";
+
+    const BRANCH_JSON: &str = r#"{
+        "type": "branch",
+        "name": "feature/refs",
+        "target": {
+            "type": "commit",
+            "hash": "012345abcdef",
+            "message": "Add refs",
+            "date": "2018-10-02T13:27:15+00:00",
+            "author": {"type": "author", "raw": "Tester <tester@example.com>"},
+            "links": {}
+        },
+        "merge_strategies": ["merge_commit", "squash", "rebase_merge"],
+        "default_merge_strategy": "squash",
+        "links": {}
+    }"#;
+
+    #[test]
+    fn lists_branches_and_tags() {
+        let tag = r#"{"type": "tag", "name": "v1.0", "message": "Release",
+            "target": {"type": "commit", "hash": "abcdef012345", "links": {}},
+            "tagger": {"raw": "Tester <tester@example.com>"}, "date": null, "links": {}}"#;
+        let transport = MemoryTransport::new();
+        transport.respond(
+            Method::Get,
+            REFS_URL,
+            Response::new(
+                200,
+                format!(
+                    r#"{{"values": [{}, {}, {{"type": "bookmark", "name": "tip"}}]}}"#,
+                    BRANCH_JSON, tag
+                )
+                .as_bytes(),
+            ),
+        );
+        let api = Api::with_transport("tester", "secret", transport);
+
+        let refs = api
+            .get(&api.repositories("team").repo_slug("repo").refs())
+            .unwrap();
+
+        match refs[0] {
+            Ref::Branch(ref branch) => {
+                assert_eq!(branch.default_merge_strategy, Some(MergeStrategy::Squash));
+                assert_eq!(branch.merge_strategies[2], MergeStrategy::RebaseMerge);
+                let target = branch.target.as_ref().unwrap();
+                assert_eq!(
+                    target.author.as_ref().unwrap().to_string(),
+                    "Tester <tester@example.com>"
+                );
+            }
+            ref other => panic!("expected branch, got {:?}", other),
+        }
+        assert_eq!(refs[1].name(), "v1.0");
+        assert_eq!(refs[1].target().unwrap().hash, "abcdef012345");
+        match refs[2] {
+            Ref::Other(ref value) => assert_eq!(value["type"], "bookmark"),
+            ref other => panic!("expected unknown ref, got {:?}", other),
+        }
+        assert_eq!(refs[2].name(), "tip");
+        assert!(refs[2].target().is_none());
+    }
+
+    #[test]
+    fn creates_and_deletes_branches_and_tags() {
+        let branch_url = format!("{}/branches/feature%2Frefs", REFS_URL);
+        let transport = MemoryTransport::new();
+        transport
+            .respond(
+                Method::Post,
+                &format!("{}/branches", REFS_URL),
+                Response::new(201, BRANCH_JSON.as_bytes()),
+            )
+            .respond(Method::Get, &branch_url, Response::new(200, BRANCH_JSON.as_bytes()))
+            .respond(Method::Delete, &branch_url, Response::new(204, b""))
+            .respond(
+                Method::Post,
+                &format!("{}/tags", REFS_URL),
+                Response::new(
+                    201,
+                    br#"{"name": "v1.0", "target": {"hash": "012345abcdef"}, "message": "Release"}"#,
+                ),
+            );
+        let api = Api::with_transport("tester", "secret", transport.clone());
+        let repository = api.repositories("team").repo_slug("repo");
+        let branches = repository.branches();
+
+        let branch = api
+            .post(&branches.create("feature/refs", "012345abcdef"))
+            .unwrap();
+        assert_eq!(
+            api.get(&branches.name(&branch.name)).unwrap()[0].name,
+            "feature/refs"
+        );
+        api.delete(&branches.name("feature/refs")).unwrap();
+
+        let mut tag = repository.tags().create("v1.0", "012345abcdef");
+        tag.message("Release");
+        assert_eq!(api.post(&tag).unwrap().message, Some("Release".to_owned()));
+
+        let requests = transport.requests();
+        let body: serde_json::Value =
+            serde_json::from_slice(requests[3].body.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({"name": "v1.0", "target": {"hash": "012345abcdef"}, "message": "Release"})
+        );
+    }
+}
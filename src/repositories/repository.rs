@@ -1,6 +1,8 @@
 use api;
 use repositories::branchrestrictions;
+use repositories::commits;
 use repositories::pullrequests;
+use repositories::refs;
 
 #[derive(Clone, Debug)]
 pub struct RepositoryBuilder {
@@ -20,16 +22,42 @@ impl RepositoryBuilder {
         pullrequests::PullrequestsBuilder::new(&self.username, &self.repo_slug)
     }
 
+    /// Commit history of all branches, or a single branch
+    pub fn commits(&self) -> commits::CommitsBuilder {
+        commits::CommitsBuilder::new(&self.url_path())
+    }
+
+    /// Single commit identified by its hash
+    pub fn commit(&self, hash: &str) -> commits::CommitBuilder {
+        commits::CommitBuilder::new(&self.url_path(), hash)
+    }
+
+    /// Branches and tags
+    pub fn refs(&self) -> refs::RefsBuilder {
+        refs::RefsBuilder::new(&self.url_path())
+    }
+
+    pub fn branches(&self) -> refs::BranchesBuilder {
+        refs::BranchesBuilder::new(&self.url_path())
+    }
+
+    pub fn tags(&self) -> refs::TagsBuilder {
+        refs::TagsBuilder::new(&self.url_path())
+    }
+
     pub fn branch_restrictions(&self) -> branchrestrictions::BranchRestrictionBuilder {
         branchrestrictions::BranchRestrictionBuilder::new(&self.username, &self.repo_slug)
     }
+
+    fn url_path(&self) -> String {
+        format!("repositories/{}/{}", self.username, self.repo_slug)
+    }
 }
 
 use repositories;
 impl api::GetQueryBuilder for RepositoryBuilder {
     type Item = repositories::Repository;
     fn get_query(&self) -> api::BitBucketQuery {
-        let url_path = format!("repositories/{}/{}", self.username, self.repo_slug,);
-        api::BitBucketQuery::new(url_path)
+        api::BitBucketQuery::new(self.url_path())
     }
 }
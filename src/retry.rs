@@ -0,0 +1,205 @@
+//! The retry module defines how the Api retries requests failing temporarily,
+//! e.g. due to rate limiting or a BitBucket hiccup.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use transport::{Method, Response};
+
+/// RetryPolicy configures the retries of failed requests, see `ApiBuilder::retry_policy`.
+///
+/// Requests are retried on network errors and on the status codes 429, 500, 502, 503 and 504,
+/// POST requests only on 429 as they might have been processed already.
+/// Between attempts the Api waits with exponential backoff, or as long as requested
+/// by a `Retry-After` header. Requests asking to wait longer than the maximum backoff
+/// are not retried.
+/// ```
+/// use bitbucket_api::api::ApiBuilder;
+/// use bitbucket_api::retry::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .backoff(Duration::from_secs(1), Duration::from_secs(60));
+/// let api = ApiBuilder::new("user", "app_password")
+///     .retry_policy(policy)
+///     .build();
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Get the default policy: 3 attempts, backoff from 500ms up to 30s with jitter.
+    pub fn new() -> RetryPolicy {
+        RetryPolicy::default()
+    }
+
+    /// Get a policy never retrying any request
+    pub fn none() -> RetryPolicy {
+        RetryPolicy::default().max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts per request, including the first one.
+    pub fn max_attempts(mut self, max_attempts: u32) -> RetryPolicy {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry, and the upper limit for all further delays,
+    /// including the ones requested by `Retry-After`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> RetryPolicy {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the factor the delay grows by with every retry.
+    pub fn multiplier(mut self, multiplier: f64) -> RetryPolicy {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Enables randomizing each delay between half and the full computed delay,
+    /// avoiding many clients retrying at the same time.
+    pub fn jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Enables waiting as long as requested by a `Retry-After` header, given in seconds.
+    /// If the requested delay exceeds the maximum backoff, the request fails instead.
+    pub fn respect_retry_after(mut self, respect_retry_after: bool) -> RetryPolicy {
+        self.respect_retry_after = respect_retry_after;
+        self
+    }
+
+    /// Decides whether the attempt-th request should be retried.
+    /// `status` is None if no response was received.
+    pub(crate) fn should_retry(&self, method: Method, attempt: u32, status: Option<u32>) -> bool {
+        if attempt >= self.max_attempts {
+            return false;
+        }
+        match status {
+            Some(429) => true,
+            _ if method == Method::Post => false,
+            Some(500) | Some(502) | Some(503) | Some(504) | None => true,
+            Some(_) => false,
+        }
+    }
+
+    /// Returns how long to wait after the attempt-th request failed,
+    /// None if the response asks to wait longer than the maximum backoff.
+    pub(crate) fn delay(&self, attempt: u32, response: Option<&Response>) -> Option<Duration> {
+        if self.respect_retry_after {
+            let retry_after = response
+                .and_then(|response| response.header("Retry-After"))
+                .and_then(|value| value.trim().parse::<u64>().ok());
+            if let Some(seconds) = retry_after {
+                let delay = Duration::from_secs(seconds);
+                return if delay <= self.max_backoff {
+                    Some(delay)
+                } else {
+                    None
+                };
+            }
+        }
+
+        let exponent = attempt.saturating_sub(1) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let mut delay = backoff.min(self.max_backoff.as_secs_f64());
+        if self.jitter {
+            delay *= 0.5 + random_fraction() / 2.0;
+        }
+        Some(Duration::from_secs_f64(delay.max(0.0)))
+    }
+}
+
+/// Returns a pseudo random number in [0, 1), good enough to spread retries.
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(nanos);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_transient_failures_only() {
+        let policy = RetryPolicy::new();
+        assert!(policy.should_retry(Method::Get, 1, Some(503)));
+        assert!(policy.should_retry(Method::Get, 1, None));
+        assert!(policy.should_retry(Method::Post, 1, Some(429)));
+        assert!(!policy.should_retry(Method::Post, 1, Some(503)));
+        assert!(!policy.should_retry(Method::Get, 1, Some(404)));
+        assert!(!policy.should_retry(Method::Get, 3, Some(503)));
+    }
+
+    #[test]
+    fn backoff_grows_up_to_max() {
+        let policy = RetryPolicy::new()
+            .backoff(Duration::from_secs(1), Duration::from_secs(5))
+            .jitter(false);
+        assert_eq!(policy.delay(1, None), Some(Duration::from_secs(1)));
+        assert_eq!(policy.delay(2, None), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay(3, None), Some(Duration::from_secs(4)));
+        assert_eq!(policy.delay(4, None), Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new().backoff(Duration::from_secs(4), Duration::from_secs(4));
+        for _ in 0..100 {
+            let delay = policy.delay(1, None).unwrap();
+            assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+        }
+    }
+
+    #[test]
+    fn retry_after_takes_precedence() {
+        let response = Response::new(429, b"").with_header("Retry-After", "7");
+        let policy = RetryPolicy::new();
+        assert_eq!(
+            policy.delay(1, Some(&response)),
+            Some(Duration::from_secs(7))
+        );
+
+        let policy = policy.respect_retry_after(false).jitter(false);
+        assert_eq!(
+            policy.delay(1, Some(&response)),
+            Some(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn long_retry_after_gives_up() {
+        let response = Response::new(429, b"").with_header("Retry-After", "3600");
+        assert_eq!(RetryPolicy::new().delay(1, Some(&response)), None);
+    }
+}
@@ -32,5 +32,6 @@ pub struct Team {
     pub username: String,
     pub display_name: String,
     pub uuid: String,
+    #[serde(default)]
     pub links: HashMap<String, api::Link>,
 }
@@ -0,0 +1,308 @@
+//! The transport module decouples the Api from the HTTP client actually talking to BitBucket.
+//!
+//! `Api` hands every request to an `HttpTransport`. By default this is the libcurl based
+//! `CurlTransport`, the `MemoryTransport` answers requests from canned responses instead,
+//! which allows testing code using `Api` without network access.
+
+use curl::easy::{Easy, List};
+use std::collections::{HashMap, VecDeque};
+use std::error;
+use std::fmt;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+/// Error type returned by transports, e.g. when the connection could not be established.
+pub type TransportError = Box<dyn error::Error + Send + Sync>;
+
+/// The HTTP methods used by the BitBucket Api
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Method::Get => write!(f, "GET"),
+            Method::Post => write!(f, "POST"),
+            Method::Put => write!(f, "PUT"),
+            Method::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+/// A single HTTP request as sent by the Api
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// HTTP method of the request
+    pub method: Method,
+    /// Absolute URL of the request
+    pub url: String,
+    /// Header names and values, in the order they will be sent
+    pub headers: Vec<(String, String)>,
+    /// Optional request body
+    pub body: Option<Vec<u8>>,
+}
+
+impl Request {
+    /// Returns the first value of the header `name`, compared case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// A single HTTP response as received by the transport
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// HTTP status code
+    pub status: u32,
+    /// Header names and values as received
+    pub headers: Vec<(String, String)>,
+    /// Raw response body
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Creates a new Response without any headers.
+    pub fn new(status: u32, body: &[u8]) -> Response {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: body.to_owned(),
+        }
+    }
+
+    /// Adds a header to the Response, useful for building canned responses.
+    pub fn with_header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Returns the first value of the header `name`, compared case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|header| header.0.eq_ignore_ascii_case(name))
+        .map(|header| header.1.as_str())
+}
+
+/// The HttpTransport Trait is implemented by everything able to send a Request to BitBucket.
+/// Implementations must not interpret the response, status codes are handled by the Api.
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+    /// Sends the request and returns the response, or an error if no response was received.
+    fn send(&self, request: &Request) -> Result<Response, TransportError>;
+}
+
+/// Default transport using libcurl
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CurlTransport;
+
+impl CurlTransport {
+    /// Get a new CurlTransport
+    pub fn new() -> CurlTransport {
+        CurlTransport
+    }
+}
+
+impl HttpTransport for CurlTransport {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        let mut handle = Easy::new();
+        handle.url(&request.url)?;
+        // Some endpoints, e.g. diffs, redirect to the actual resource
+        handle.follow_location(true)?;
+        match request.method {
+            Method::Get => handle.get(true)?,
+            Method::Post => handle.post(true)?,
+            Method::Put => handle.custom_request("PUT")?,
+            Method::Delete => handle.custom_request("DELETE")?,
+        }
+        let mut headers = List::new();
+        for header in &request.headers {
+            headers.append(&format!("{}: {}", header.0, header.1))?;
+        }
+        handle.http_headers(headers)?;
+        match request.body {
+            Some(ref body) => handle.post_fields_copy(body)?,
+            None if request.method == Method::Post => handle.post_field_size(0)?,
+            None => {}
+        }
+
+        let mut body = Vec::new();
+        let mut response_headers = Vec::new();
+        {
+            let mut transfer = handle.transfer();
+            transfer.write_function(|data| {
+                body.extend_from_slice(data);
+                Ok(data.len())
+            })?;
+            transfer.header_function(|line| {
+                parse_header_line(&mut response_headers, line);
+                true
+            })?;
+            transfer.perform()?;
+        }
+
+        Ok(Response {
+            status: handle.response_code()?,
+            headers: response_headers,
+            body,
+        })
+    }
+}
+
+fn parse_header_line(headers: &mut Vec<(String, String)>, line: &[u8]) {
+    let line = match str::from_utf8(line) {
+        Ok(line) => line.trim(),
+        Err(_) => return,
+    };
+    // Every status line starts a new header block, e.g. after "100 Continue"
+    if line.starts_with("HTTP/") {
+        headers.clear();
+    } else if let Some(pos) = line.find(':') {
+        let (name, value) = line.split_at(pos);
+        headers.push((name.trim().to_owned(), value[1..].trim().to_owned()));
+    }
+}
+
+/// In-memory transport answering requests with previously registered responses.
+///
+/// Clones share the same responses and request log, so a clone can be handed to the Api
+/// while the original is used to inspect the requests sent.
+/// ```
+/// use bitbucket_api::api::Api;
+/// use bitbucket_api::transport::{MemoryTransport, Method, Response};
+///
+/// let transport = MemoryTransport::new();
+/// transport.respond(
+///     Method::Get,
+///     "https://api.bitbucket.org/2.0/teams/atlassian",
+///     Response::new(200, br#"{"username":"atlassian","display_name":"Atlassian","uuid":"{1}","links":{}}"#),
+/// );
+///
+/// let api = Api::with_transport("user", "app_password", transport.clone());
+/// let team = api.get(&api.team("atlassian")).unwrap();
+///
+/// assert_eq!(team[0].display_name, "Atlassian");
+/// assert_eq!(transport.requests().len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransport {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    responses: HashMap<(Method, String), VecDeque<Response>>,
+    requests: Vec<Request>,
+}
+
+impl MemoryTransport {
+    /// Get a new MemoryTransport without any registered responses
+    pub fn new() -> MemoryTransport {
+        MemoryTransport::default()
+    }
+
+    /// Registers a response for the given method and absolute URL.
+    /// Multiple responses for the same request are returned in order,
+    /// the last one is repeated for all further requests.
+    pub fn respond(&self, method: Method, url: &str, response: Response) -> &MemoryTransport {
+        let mut state = self.state.lock().expect("MemoryTransport lock poisoned");
+        state
+            .responses
+            .entry((method, url.to_owned()))
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    /// Returns all requests sent through this transport so far.
+    pub fn requests(&self) -> Vec<Request> {
+        let state = self.state.lock().expect("MemoryTransport lock poisoned");
+        state.requests.clone()
+    }
+}
+
+impl HttpTransport for MemoryTransport {
+    fn send(&self, request: &Request) -> Result<Response, TransportError> {
+        let mut state = self.state.lock().expect("MemoryTransport lock poisoned");
+        state.requests.push(request.clone());
+        let key = (request.method, request.url.to_owned());
+        match state.responses.get_mut(&key) {
+            Some(ref mut queue) if queue.len() > 1 => Ok(queue.pop_front().unwrap()),
+            Some(ref queue) if !queue.is_empty() => Ok(queue[0].clone()),
+            _ => Err(format!(
+                "No response registered for {} {}",
+                request.method, request.url
+            )
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_transport_replays_responses_in_order() {
+        let transport = MemoryTransport::new();
+        transport
+            .respond(Method::Get, "http://localhost/a", Response::new(503, b""))
+            .respond(Method::Get, "http://localhost/a", Response::new(200, b"ok"));
+
+        let request = Request {
+            method: Method::Get,
+            url: "http://localhost/a".to_owned(),
+            headers: Vec::new(),
+            body: None,
+        };
+        let statuses: Vec<u32> = (0..3)
+            .map(|_| transport.send(&request).unwrap().status)
+            .collect();
+
+        assert_eq!(statuses, vec![503, 200, 200]);
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn memory_transport_rejects_unknown_requests() {
+        let transport = MemoryTransport::new();
+        let request = Request {
+            method: Method::Delete,
+            url: "http://localhost/a".to_owned(),
+            headers: Vec::new(),
+            body: None,
+        };
+        assert!(transport.send(&request).is_err());
+    }
+
+    #[test]
+    fn header_lookup_ignores_case() {
+        let response = Response::new(200, b"").with_header("Retry-After", "3");
+        assert_eq!(response.header("retry-after"), Some("3"));
+        assert_eq!(response.header("x-missing"), None);
+    }
+
+    #[test]
+    fn header_lines_restart_on_status_line() {
+        let mut headers = Vec::new();
+        parse_header_line(&mut headers, b"HTTP/1.1 100 Continue\r\n");
+        parse_header_line(&mut headers, b"X-Stale: 1\r\n");
+        parse_header_line(&mut headers, b"HTTP/1.1 200 OK\r\n");
+        parse_header_line(&mut headers, b"Content-Type: application/json\r\n");
+        parse_header_line(&mut headers, b"\r\n");
+        assert_eq!(
+            headers,
+            vec![("Content-Type".to_owned(), "application/json".to_owned())]
+        );
+    }
+}
@@ -32,8 +32,9 @@ pub struct User {
     pub account_id: Option<String>,
     pub uuid: String,
     pub account_status: Option<String>,
-    pub created_on: Option<String>,
+    pub created_on: Option<api::Timestamp>,
     pub is_staff: Option<bool>,
+    #[serde(default)]
     pub links: HashMap<String, api::Link>,
     pub location: Option<String>,
     pub website: Option<String>,